@@ -1,5 +1,5 @@
 use std::collections::BTreeSet;
-use std::fs::read_dir;
+use std::fs::{read_dir, read_to_string};
 use std::io;
 use std::path::Path;
 
@@ -13,6 +13,15 @@ pub trait AbstractFilesystem {
     /// This method should return a [std::io::ErrorKind::NotFound] error if the
     /// directory does not exist.
     fn file_names_in<T: AsRef<Path>>(&self, rel_path: T) -> io::Result<BTreeSet<Box<str>>>;
+
+    /// Returns the contents of the given file.
+    ///
+    /// This is used to load other manifests, e.g. during workspace root discovery.
+    /// The default implementation returns an [std::io::ErrorKind::Unsupported] error.
+    fn read_to_string<T: AsRef<Path>>(&self, rel_path: T) -> io::Result<String> {
+        let _ = rel_path;
+        Err(io::Error::from(io::ErrorKind::Unsupported))
+    }
 }
 
 /// A [AbstractFilesystem] implementation that reads from the actual filesystem
@@ -37,4 +46,8 @@ impl AbstractFilesystem for Filesystem<'_> {
             })
             .collect())
     }
+
+    fn read_to_string<T: AsRef<Path>>(&self, rel_path: T) -> io::Result<String> {
+        read_to_string(self.path.join(rel_path))
+    }
 }
@@ -0,0 +1,361 @@
+//! Parsing and evaluation of the platform keys of `[target]` tables, i.e. target
+//! triples and `cfg(...)` expressions
+//! (see <https://doc.rust-lang.org/cargo/reference/specifying-dependencies.html#platform-specific-dependencies>).
+
+use crate::{quote_key, Error, Manifest, Target};
+use std::collections::BTreeSet;
+use std::fmt;
+use std::iter::Peekable;
+use std::str::{CharIndices, FromStr};
+
+/// A single cfg value, e.g. `unix` or `target_os = "linux"`.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Cfg {
+    /// A name without a value, e.g. `unix`.
+    Name(String),
+    /// A key with a value, e.g. `target_os = "linux"`.
+    KeyPair(String, String),
+}
+
+/// A `cfg` expression, i.e. the contents of `cfg(...)`.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum CfgExpr {
+    Not(Box<CfgExpr>),
+    All(Vec<CfgExpr>),
+    Any(Vec<CfgExpr>),
+    Value(Cfg),
+}
+
+/// The key of a `[target.<platform>]` table.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Platform {
+    /// A target triple, e.g. `x86_64-pc-windows-gnu`.
+    Name(String),
+    /// A `cfg(...)` expression.
+    Cfg(CfgExpr),
+}
+
+/// The platform that is being compiled for, described by its target triple and cfg values.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct TargetPlatform {
+    pub triple: String,
+    pub cfgs: BTreeSet<Cfg>,
+}
+
+impl CfgExpr {
+    /// Evaluates the expression against the given set of cfg values.
+    pub fn eval(&self, cfgs: &BTreeSet<Cfg>) -> bool {
+        match self {
+            CfgExpr::Not(expr) => !expr.eval(cfgs),
+            CfgExpr::All(exprs) => exprs.iter().all(|expr| expr.eval(cfgs)),
+            CfgExpr::Any(exprs) => exprs.iter().any(|expr| expr.eval(cfgs)),
+            CfgExpr::Value(cfg) => cfgs.contains(cfg),
+        }
+    }
+}
+
+impl Platform {
+    /// Returns whether this platform applies to the given target platform.
+    pub fn matches(&self, target: &TargetPlatform) -> bool {
+        match self {
+            Platform::Name(name) => *name == target.triple,
+            Platform::Cfg(expr) => expr.eval(&target.cfgs),
+        }
+    }
+}
+
+impl TargetPlatform {
+    pub fn new(triple: impl Into<String>, cfgs: impl IntoIterator<Item = Cfg>) -> Self {
+        Self {
+            triple: triple.into(),
+            cfgs: cfgs.into_iter().collect(),
+        }
+    }
+
+    /// Creates a target platform from the output of `rustc --print cfg`, which
+    /// contains one cfg value per line, e.g. `target_os="linux"`.
+    pub fn from_rustc_cfg(triple: impl Into<String>, output: &str) -> Result<Self, Error> {
+        let cfgs = output
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(str::parse)
+            .collect::<Result<_, _>>()?;
+        Ok(Self {
+            triple: triple.into(),
+            cfgs,
+        })
+    }
+
+    /// Returns whether the given `[target]` table key applies to this platform.
+    pub fn matches(&self, platform: &Platform) -> bool {
+        platform.matches(self)
+    }
+}
+
+impl<PackageMetadata, WorkspaceMetadata> Manifest<PackageMetadata, WorkspaceMetadata> {
+    /// Returns the `[target.<platform>]` tables that apply to the given platform.
+    ///
+    /// Returns [Error::InvalidCfgExpr] if a key of the `[target]` table is neither
+    /// a target triple nor a valid `cfg(...)` expression.
+    pub fn active_targets(&self, platform: &TargetPlatform) -> Result<Vec<(&str, &Target)>, Error> {
+        let mut active = Vec::new();
+        for (key, target) in self.target.iter().flatten() {
+            let parsed: Platform = key
+                .parse()
+                .map_err(|err: Error| err.at_key(&format!("target.{}", quote_key(key))))?;
+            if parsed.matches(platform) {
+                active.push((key.as_str(), target));
+            }
+        }
+        Ok(active)
+    }
+}
+
+impl FromStr for Platform {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Error> {
+        if let Some(expr) = s.strip_prefix("cfg(").and_then(|s| s.strip_suffix(')')) {
+            let expr = expr.parse().map_err(|err| with_expr(err, s))?;
+            return Ok(Platform::Cfg(expr));
+        }
+
+        let is_valid = |c: char| c.is_alphanumeric() || matches!(c, '_' | '-' | '.');
+        if s.is_empty() || !s.chars().all(is_valid) {
+            return Err(invalid(
+                s,
+                "expected a target triple or a `cfg(...)` expression",
+            ));
+        }
+        Ok(Platform::Name(s.to_string()))
+    }
+}
+
+impl FromStr for CfgExpr {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Error> {
+        let mut parser = Parser::new(s);
+        let expr = parser.expr()?;
+        match parser.next()? {
+            None => Ok(expr),
+            Some(token) => Err(parser.error(&format!("unexpected {token} after the expression"))),
+        }
+    }
+}
+
+impl FromStr for Cfg {
+    type Err = Error;
+
+    /// Parses a cfg value in the format of `rustc --print cfg`, e.g. `target_os="linux"`.
+    fn from_str(s: &str) -> Result<Self, Error> {
+        match s.parse()? {
+            CfgExpr::Value(cfg) => Ok(cfg),
+            _ => Err(invalid(s, "expected a cfg value, not an expression")),
+        }
+    }
+}
+
+impl fmt::Display for Cfg {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Cfg::Name(name) => f.write_str(name),
+            Cfg::KeyPair(key, value) => write!(f, "{key} = \"{value}\""),
+        }
+    }
+}
+
+impl fmt::Display for CfgExpr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let list = |f: &mut fmt::Formatter<'_>, name: &str, exprs: &[CfgExpr]| {
+            write!(f, "{name}(")?;
+            for (i, expr) in exprs.iter().enumerate() {
+                if i > 0 {
+                    f.write_str(", ")?;
+                }
+                expr.fmt(f)?;
+            }
+            f.write_str(")")
+        };
+        match self {
+            CfgExpr::Not(expr) => write!(f, "not({expr})"),
+            CfgExpr::All(exprs) => list(f, "all", exprs),
+            CfgExpr::Any(exprs) => list(f, "any", exprs),
+            CfgExpr::Value(cfg) => cfg.fmt(f),
+        }
+    }
+}
+
+impl fmt::Display for Platform {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Platform::Name(name) => f.write_str(name),
+            Platform::Cfg(expr) => write!(f, "cfg({expr})"),
+        }
+    }
+}
+
+fn invalid(expr: &str, reason: &str) -> Error {
+    Error::InvalidCfgExpr {
+        expr: expr.to_string(),
+        reason: reason.to_string(),
+    }
+}
+
+/// Replaces the expression of an [Error::InvalidCfgExpr] with the full platform key.
+fn with_expr(err: Error, expr: &str) -> Error {
+    match err {
+        Error::InvalidCfgExpr { reason, .. } => invalid(expr, &reason),
+        err => err,
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Token<'a> {
+    LeftParen,
+    RightParen,
+    Comma,
+    Equals,
+    Ident(&'a str),
+    String(&'a str),
+}
+
+impl fmt::Display for Token<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::LeftParen => f.write_str("`(`"),
+            Token::RightParen => f.write_str("`)`"),
+            Token::Comma => f.write_str("`,`"),
+            Token::Equals => f.write_str("`=`"),
+            Token::Ident(ident) => write!(f, "identifier `{ident}`"),
+            Token::String(s) => write!(f, "string \"{s}\""),
+        }
+    }
+}
+
+struct Parser<'a> {
+    input: &'a str,
+    chars: Peekable<CharIndices<'a>>,
+    peeked: Option<Token<'a>>,
+}
+
+impl<'a> Parser<'a> {
+    fn new(input: &'a str) -> Self {
+        Self {
+            input,
+            chars: input.char_indices().peekable(),
+            peeked: None,
+        }
+    }
+
+    fn error(&self, reason: &str) -> Error {
+        invalid(self.input, reason)
+    }
+
+    fn expr(&mut self) -> Result<CfgExpr, Error> {
+        match self.next()? {
+            Some(Token::Ident(op @ ("all" | "any"))) => {
+                self.expect(Token::LeftParen)?;
+                let mut exprs = Vec::new();
+                loop {
+                    if self.peek()? == Some(&Token::RightParen) {
+                        break;
+                    }
+                    exprs.push(self.expr()?);
+                    if self.peek()? != Some(&Token::Comma) {
+                        break;
+                    }
+                    self.next()?;
+                }
+                self.expect(Token::RightParen)?;
+                Ok(if op == "all" {
+                    CfgExpr::All(exprs)
+                } else {
+                    CfgExpr::Any(exprs)
+                })
+            }
+            Some(Token::Ident("not")) => {
+                self.expect(Token::LeftParen)?;
+                let expr = self.expr()?;
+                self.expect(Token::RightParen)?;
+                Ok(CfgExpr::Not(Box::new(expr)))
+            }
+            Some(Token::Ident(name)) => {
+                if self.peek()? != Some(&Token::Equals) {
+                    return Ok(CfgExpr::Value(Cfg::Name(name.to_string())));
+                }
+                self.next()?;
+                match self.next()? {
+                    Some(Token::String(value)) => Ok(CfgExpr::Value(Cfg::KeyPair(
+                        name.to_string(),
+                        value.to_string(),
+                    ))),
+                    Some(token) => Err(self.error(&format!("expected a string, found {token}"))),
+                    None => Err(self.error("expected a string, found the end of the expression")),
+                }
+            }
+            Some(token) => Err(self.error(&format!("expected an identifier, found {token}"))),
+            None => Err(self.error("expected an identifier, found the end of the expression")),
+        }
+    }
+
+    fn expect(&mut self, expected: Token<'_>) -> Result<(), Error> {
+        match self.next()? {
+            Some(token) if token == expected => Ok(()),
+            Some(token) => Err(self.error(&format!("expected {expected}, found {token}"))),
+            None => Err(self.error(&format!(
+                "expected {expected}, found the end of the expression"
+            ))),
+        }
+    }
+
+    fn peek(&mut self) -> Result<Option<&Token<'a>>, Error> {
+        if self.peeked.is_none() {
+            self.peeked = self.token()?;
+        }
+        Ok(self.peeked.as_ref())
+    }
+
+    fn next(&mut self) -> Result<Option<Token<'a>>, Error> {
+        match self.peeked.take() {
+            Some(token) => Ok(Some(token)),
+            None => self.token(),
+        }
+    }
+
+    fn token(&mut self) -> Result<Option<Token<'a>>, Error> {
+        while let Some((start, c)) = self.chars.next() {
+            let token = match c {
+                ' ' | '\t' | '\n' | '\r' => continue,
+                '(' => Token::LeftParen,
+                ')' => Token::RightParen,
+                ',' => Token::Comma,
+                '=' => Token::Equals,
+                '"' => {
+                    let end = loop {
+                        match self.chars.next() {
+                            Some((end, '"')) => break end,
+                            Some(_) => {}
+                            None => return Err(self.error("unterminated string")),
+                        }
+                    };
+                    Token::String(&self.input[start + 1..end])
+                }
+                c if c.is_alphabetic() || c == '_' => {
+                    let mut end = start + c.len_utf8();
+                    while let Some((i, c)) = self
+                        .chars
+                        .next_if(|(_, c)| c.is_alphanumeric() || *c == '_')
+                    {
+                        end = i + c.len_utf8();
+                    }
+                    Token::Ident(&self.input[start..end])
+                }
+                c => return Err(self.error(&format!("unexpected character `{c}`"))),
+            };
+            return Ok(Some(token));
+        }
+        Ok(None)
+    }
+}
@@ -0,0 +1,62 @@
+//! Queries for the dependencies that are compiled for a given platform and set of features.
+
+use crate::{Dependency, DependencyKind, Error, Manifest, TargetPlatform};
+use std::collections::BTreeSet;
+
+/// A dependency that is compiled for a given platform and set of features,
+/// see [Manifest::active_dependencies()].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ActiveDependency<'a> {
+    /// The key of the dependency in its table.
+    pub name: &'a str,
+    pub dependency: &'a Dependency,
+    pub kind: DependencyKind,
+    /// The key of the `[target.<platform>]` table that declares the dependency,
+    /// or `None` for the top-level dependency tables.
+    pub target: Option<&'a str>,
+}
+
+impl<PackageMetadata, WorkspaceMetadata> Manifest<PackageMetadata, WorkspaceMetadata> {
+    /// Returns the dependencies of the given kinds that are compiled for the given
+    /// platform when the given features of this package are enabled.
+    ///
+    /// This includes the top-level dependency tables and all `[target]` tables that
+    /// apply to the platform, see [Manifest::active_targets()]. Optional dependencies
+    /// are only returned if one of the enabled features enables them.
+    ///
+    /// Note that `default` is not enabled implicitly, pass it in `features` to
+    /// enable the default features. See [Manifest::resolve_features()] for how the
+    /// enabled optional dependencies are determined.
+    ///
+    /// Returns [Error::UnknownFeature] if one of the features is not defined.
+    pub fn active_dependencies(
+        &self,
+        platform: &TargetPlatform,
+        features: &[&str],
+        kinds: &[DependencyKind],
+    ) -> Result<Vec<ActiveDependency<'_>>, Error> {
+        let enabled = self.resolve_features(features, false)?.dependencies;
+
+        let active_targets = self
+            .active_targets(platform)?
+            .into_iter()
+            .map(|(key, _)| key)
+            .collect::<BTreeSet<_>>();
+        let active = self
+            .dependency_tables()
+            .into_iter()
+            .filter(|(target, _, _)| target.is_none_or(|key| active_targets.contains(key)))
+            .filter(|(_, kind, _)| kinds.contains(kind))
+            .flat_map(|(target, kind, deps)| {
+                deps.iter().map(move |(name, dependency)| ActiveDependency {
+                    name,
+                    dependency,
+                    kind,
+                    target,
+                })
+            })
+            .filter(|dep| !dep.dependency.optional() || enabled.contains(dep.name))
+            .collect();
+        Ok(active)
+    }
+}
@@ -0,0 +1,227 @@
+use crate::{quote_key, Dependency, DependencyKind, Error, Manifest, Value};
+use std::fmt;
+use std::str::FromStr;
+use toml_edit::{DocumentMut, Item, TableLike};
+
+/// A `Cargo.toml` file that can be edited without losing its formatting.
+///
+/// [Manifest] is a great fit for reading manifests, but serializing it with `toml`
+/// discards comments, key order and whitespace of the original file. This type
+/// keeps the original document around and only touches the parts of it that are
+/// changed by the typed edit methods, so that the rest of the file is written
+/// back verbatim.
+///
+/// Use [ManifestDocument::manifest()] to get a typed view of the current contents,
+/// and the [Display](fmt::Display) implementation to write the document back.
+#[derive(Debug, Clone)]
+pub struct ManifestDocument {
+    document: DocumentMut,
+}
+
+impl FromStr for ManifestDocument {
+    type Err = Error;
+
+    /// Parse contents of a `Cargo.toml` file loaded as a string
+    fn from_str(cargo_toml_content: &str) -> Result<Self, Self::Err> {
+        let document = cargo_toml_content
+            .parse()
+            .map_err(|err| Error::from(err).in_content(cargo_toml_content))?;
+        Ok(Self { document })
+    }
+}
+
+impl fmt::Display for ManifestDocument {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.document.fmt(f)
+    }
+}
+
+impl ManifestDocument {
+    /// Parses the current contents of the document into a [Manifest].
+    pub fn manifest(&self) -> Result<Manifest, Error> {
+        Manifest::from_str(&self.document.to_string())
+    }
+
+    /// Sets `package.version`, replacing `version.workspace = true` if necessary.
+    ///
+    /// Returns [Error::MissingKey] if the manifest has no `[package]` table.
+    pub fn set_version(&mut self, version: &str) -> Result<(), Error> {
+        // Some old crates use `[project]` instead of `[package]`
+        let table_name = ["package", "project"]
+            .into_iter()
+            .find(|name| self.document.contains_key(name))
+            .unwrap_or("package");
+        let Some(package) = self
+            .document
+            .get_mut(table_name)
+            .and_then(Item::as_table_like_mut)
+        else {
+            return Err(self.missing_key("package"));
+        };
+
+        set_value(package, "version", version.into());
+        Ok(())
+    }
+
+    /// Adds a dependency to the `[dependencies]`, `[dev-dependencies]` or
+    /// `[build-dependencies]` table, replacing any existing dependency with the same name.
+    ///
+    /// The table is created if it doesn't exist yet.
+    pub fn add_dependency(
+        &mut self,
+        kind: DependencyKind,
+        name: &str,
+        dependency: &Dependency,
+    ) -> Result<(), Error> {
+        let value = match Value::try_from(dependency.clone()) {
+            Ok(value) => to_edit_value(&value),
+            Err(err) => {
+                let key = format!("{}.{}", kind.table_name(), quote_key(name));
+                return Err(Error::from(err).at_key_in(&key, &self.document.to_string()));
+            }
+        };
+        let table = match self.dependency_table_name(kind) {
+            Some(table_name) => {
+                let Some(table) = self.document[table_name].as_table_like_mut() else {
+                    return Err(self.missing_key(table_name));
+                };
+                table
+            }
+            None => self.document[kind.table_name()]
+                .or_insert(toml_edit::table())
+                .as_table_like_mut()
+                .expect("a table was just inserted"),
+        };
+
+        set_value(table, name, value);
+        Ok(())
+    }
+
+    /// Removes a dependency from the table of the given kind.
+    ///
+    /// Returns `false` if there was no such dependency.
+    pub fn remove_dependency(&mut self, kind: DependencyKind, name: &str) -> bool {
+        self.dependency_table_mut(kind)
+            .and_then(|table| table.remove(name))
+            .is_some()
+    }
+
+    /// Enables or disables a feature of a dependency.
+    ///
+    /// A dependency that is specified as a plain version requirement is converted
+    /// to an inline table if a feature needs to be added.
+    ///
+    /// Returns [Error::MissingKey] if there is no such dependency.
+    pub fn set_dependency_feature(
+        &mut self,
+        kind: DependencyKind,
+        name: &str,
+        feature: &str,
+        enabled: bool,
+    ) -> Result<(), Error> {
+        let key = format!("{}.{}", kind.table_name(), quote_key(name));
+        let Some(item) = self.dependency_mut(kind, name) else {
+            return Err(self.missing_key(&key));
+        };
+
+        if let Some(version) = item.as_str() {
+            if !enabled {
+                return Ok(());
+            }
+            let mut table = toml_edit::InlineTable::new();
+            table.insert("version", version.into());
+            let decor = item.as_value().map(|v| v.decor().clone());
+            *item = Item::Value(table.into());
+            if let (Some(decor), Some(value)) = (decor, item.as_value_mut()) {
+                *value.decor_mut() = decor;
+            }
+        }
+
+        let Some(table) = item.as_table_like_mut() else {
+            return Err(self.missing_key(&key));
+        };
+
+        if enabled {
+            let features = table
+                .entry("features")
+                .or_insert(Item::Value(toml_edit::Array::new().into()));
+            if let Some(features) = features.as_array_mut() {
+                if !features.iter().any(|f| f.as_str() == Some(feature)) {
+                    features.push(feature);
+                }
+            }
+        } else if let Some(features) = table.get_mut("features").and_then(Item::as_array_mut) {
+            // Keep the formatting of the first element if it gets removed
+            let first_prefix = features.get(0).and_then(|f| f.decor().prefix().cloned());
+            features.retain(|f| f.as_str() != Some(feature));
+            if let (Some(first), Some(prefix)) = (features.get_mut(0), first_prefix) {
+                first.decor_mut().set_prefix(prefix);
+            }
+            if features.is_empty() {
+                table.remove("features");
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the name of the existing table for the given kind of dependencies,
+    /// taking the legacy `snake_case` spelling into account.
+    fn dependency_table_name(&self, kind: DependencyKind) -> Option<&'static str> {
+        [kind.table_name(), kind.legacy_table_name()]
+            .into_iter()
+            .find(|name| self.document.contains_key(name))
+    }
+
+    fn dependency_table_mut(&mut self, kind: DependencyKind) -> Option<&mut dyn TableLike> {
+        let table_name = self.dependency_table_name(kind)?;
+        self.document[table_name].as_table_like_mut()
+    }
+
+    fn dependency_mut(&mut self, kind: DependencyKind, name: &str) -> Option<&mut Item> {
+        self.dependency_table_mut(kind)?.get_mut(name)
+    }
+
+    /// Returns [Error::MissingKey] with the location of the key in the current document,
+    /// which is only known if the key exists but doesn't have the expected type.
+    fn missing_key(&self, key: &str) -> Error {
+        Error::MissingKey {
+            key: key.to_string(),
+        }
+        .at_key_in(key, &self.document.to_string())
+    }
+}
+
+/// Sets a value in a table, keeping the formatting of an existing value.
+fn set_value(table: &mut dyn TableLike, key: &str, mut value: toml_edit::Value) {
+    match table.get_mut(key) {
+        Some(Item::Value(existing)) => {
+            *value.decor_mut() = existing.decor().clone();
+            *existing = value;
+        }
+        Some(item) => *item = Item::Value(value),
+        None => {
+            table.insert(key, Item::Value(value));
+        }
+    }
+}
+
+pub(crate) fn to_edit_value(value: &Value) -> toml_edit::Value {
+    match value {
+        Value::String(s) => s.into(),
+        Value::Integer(i) => (*i).into(),
+        Value::Float(f) => (*f).into(),
+        Value::Boolean(b) => (*b).into(),
+        Value::Datetime(dt) => (*dt).into(),
+        Value::Array(array) => array
+            .iter()
+            .map(to_edit_value)
+            .collect::<toml_edit::Array>()
+            .into(),
+        Value::Table(table) => table
+            .iter()
+            .map(|(k, v)| (k, to_edit_value(v)))
+            .collect::<toml_edit::InlineTable>()
+            .into(),
+    }
+}
@@ -1,24 +1,186 @@
+use crate::Location;
 use std::io;
+use std::path::PathBuf;
 
+/// The errors returned by this crate.
+///
+/// Errors that can be traced back to a manifest, e.g. parse errors, errors of the
+/// workspace loader and errors of [ManifestDocument](crate::ManifestDocument) edits,
+/// are wrapped in [Error::Located]. Use [Error::without_location()] to match on the
+/// kind of error. Invalid values of profile settings and of the `artifact` key of
+/// dependencies are reported with their own variant, e.g. [Error::InvalidStripSetting],
+/// rather than as [Error::Parse]:
+///
+/// ```
+/// use cargo_manifest::{Error, Manifest};
+/// use std::str::FromStr;
+///
+/// let err = Manifest::from_str("[package]\nname = 1").unwrap_err();
+/// assert!(matches!(err.without_location(), Error::Parse(_)));
+/// assert_eq!(err.location().unwrap().key.as_deref(), Some("package.name"));
+/// ```
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
     #[error(transparent)]
     Parse(#[from] toml::de::Error),
     #[error(transparent)]
+    Edit(#[from] toml_edit::TomlError),
+    #[error(transparent)]
+    Serialize(#[from] toml::ser::Error),
+    #[error(transparent)]
     Io(#[from] io::Error),
     #[error(transparent)]
     Utf8(#[from] std::str::Utf8Error),
-    #[error("{0}")]
-    Other(String),
+    #[error("`{key}` is not defined in the manifest")]
+    MissingKey { key: String },
+    #[error("`{key}` is inherited from the workspace, but the workspace does not define it")]
+    MissingWorkspaceKey { key: String },
+    #[error("`{}` are inherited from the workspace, but the workspace does not define them", keys.join("`, `"))]
+    MissingWorkspaceKeys { keys: Vec<String> },
+    #[error("`{key}` cannot be inherited from the workspace and defined locally at the same time")]
+    InvalidWorkspaceInheritance { key: String },
+    #[error("`{}` does not define a workspace", path.display())]
+    NotAWorkspaceRoot { path: PathBuf },
+    #[error("`{}` is not a member of the workspace defined by `{}`", member.display(), root.display())]
+    NotAWorkspaceMember { member: PathBuf, root: PathBuf },
+    #[error("workspace member `{}` does not contain a `Cargo.toml` file", path.display())]
+    MissingMemberManifest { path: PathBuf },
+    #[error("invalid glob pattern `{pattern}`: {reason}")]
+    InvalidGlob { pattern: String, reason: String },
+    #[error(
+        "can't find library, rename file to {} or specify lib.path",
+        one_of(expected_paths)
+    )]
+    MissingLibraryTarget { expected_paths: Vec<String> },
+    #[error("'{0}' is not a valid value for 'strip'")]
+    InvalidStripSetting(String),
+    #[error("'{0}' is not a valid value for 'opt-level'")]
+    InvalidOptLevel(String),
+    #[error("'{0}' is not a valid value for 'debug'")]
+    InvalidDebugInfo(String),
+    #[error("'{0}' is not a valid value for 'lto'")]
+    InvalidLto(String),
+    #[error("'{0}' is not a valid value for 'artifact'")]
+    InvalidArtifactKind(String),
+    #[error("the package does not have the feature `{feature}`")]
+    UnknownFeature { feature: String },
+    #[error("invalid platform `{expr}`: {reason}")]
+    InvalidCfgExpr { expr: String, reason: String },
+    #[error("failed to parse the version `{version}`: {reason}")]
+    InvalidVersion { version: String, reason: String },
+    #[error("failed to parse the version requirement `{req}`: {reason}")]
+    InvalidVersionReq { req: String, reason: String },
+    #[error("the version requirement of a dependency with `workspace = true` is defined by the workspace")]
+    InheritedVersionReq,
+    #[error("invalid dependency source: {reason}")]
+    InvalidDependencySource { reason: String },
+    #[error("invalid package spec `{spec}`: {reason}")]
+    InvalidPackageSpec { spec: String, reason: String },
+    #[error("profile `{name}` is not defined")]
+    UnknownProfile { name: String },
+    #[error("profile `{name}` is missing an `inherits` directive (`inherits` is required for all profiles except `dev` or `release`)")]
+    MissingProfileInherits { name: String },
+    #[error("profile inheritance loop detected: {}", chain.join(" -> "))]
+    ProfileInheritanceCycle { chain: Vec<String> },
+    #[error("`{key}` may not be specified in a `{which}` profile")]
+    InvalidProfileOverride { key: String, which: String },
+    /// An error together with the [Location] in the manifest where it occurred.
+    ///
+    /// This wraps the other variants, so matching on them directly only works
+    /// for errors without a location.
+    #[error("{location}: {source}")]
+    Located {
+        location: Box<Location>,
+        source: Box<Error>,
+    },
 }
 
 impl Clone for Error {
     fn clone(&self) -> Self {
         match self {
             Error::Parse(ref err) => Error::Parse(err.clone()),
+            Error::Edit(ref err) => Error::Edit(err.clone()),
+            Error::Serialize(ref err) => Error::Serialize(err.clone()),
             Error::Io(ref err) => Error::Io(io::Error::new(err.kind(), err.to_string())),
             Error::Utf8(ref err) => Error::Utf8(*err),
-            Error::Other(msg) => Error::Other(msg.clone()),
+            Error::MissingKey { key } => Error::MissingKey { key: key.clone() },
+            Error::MissingWorkspaceKey { key } => Error::MissingWorkspaceKey { key: key.clone() },
+            Error::MissingWorkspaceKeys { keys } => {
+                Error::MissingWorkspaceKeys { keys: keys.clone() }
+            }
+            Error::InvalidWorkspaceInheritance { key } => {
+                Error::InvalidWorkspaceInheritance { key: key.clone() }
+            }
+            Error::NotAWorkspaceRoot { path } => Error::NotAWorkspaceRoot { path: path.clone() },
+            Error::NotAWorkspaceMember { member, root } => Error::NotAWorkspaceMember {
+                member: member.clone(),
+                root: root.clone(),
+            },
+            Error::MissingMemberManifest { path } => {
+                Error::MissingMemberManifest { path: path.clone() }
+            }
+            Error::InvalidGlob { pattern, reason } => Error::InvalidGlob {
+                pattern: pattern.clone(),
+                reason: reason.clone(),
+            },
+            Error::MissingLibraryTarget { expected_paths } => Error::MissingLibraryTarget {
+                expected_paths: expected_paths.clone(),
+            },
+            Error::InvalidStripSetting(value) => Error::InvalidStripSetting(value.clone()),
+            Error::InvalidOptLevel(value) => Error::InvalidOptLevel(value.clone()),
+            Error::InvalidDebugInfo(value) => Error::InvalidDebugInfo(value.clone()),
+            Error::InvalidLto(value) => Error::InvalidLto(value.clone()),
+            Error::InvalidArtifactKind(value) => Error::InvalidArtifactKind(value.clone()),
+            Error::UnknownFeature { feature } => Error::UnknownFeature {
+                feature: feature.clone(),
+            },
+            Error::InvalidCfgExpr { expr, reason } => Error::InvalidCfgExpr {
+                expr: expr.clone(),
+                reason: reason.clone(),
+            },
+            Error::InvalidVersion { version, reason } => Error::InvalidVersion {
+                version: version.clone(),
+                reason: reason.clone(),
+            },
+            Error::InvalidVersionReq { req, reason } => Error::InvalidVersionReq {
+                req: req.clone(),
+                reason: reason.clone(),
+            },
+            Error::InheritedVersionReq => Error::InheritedVersionReq,
+            Error::InvalidDependencySource { reason } => Error::InvalidDependencySource {
+                reason: reason.clone(),
+            },
+            Error::InvalidPackageSpec { spec, reason } => Error::InvalidPackageSpec {
+                spec: spec.clone(),
+                reason: reason.clone(),
+            },
+            Error::UnknownProfile { name } => Error::UnknownProfile { name: name.clone() },
+            Error::MissingProfileInherits { name } => {
+                Error::MissingProfileInherits { name: name.clone() }
+            }
+            Error::ProfileInheritanceCycle { chain } => Error::ProfileInheritanceCycle {
+                chain: chain.clone(),
+            },
+            Error::InvalidProfileOverride { key, which } => Error::InvalidProfileOverride {
+                key: key.clone(),
+                which: which.clone(),
+            },
+            Error::Located { location, source } => Error::Located {
+                location: location.clone(),
+                source: source.clone(),
+            },
         }
     }
 }
+
+/// Formats a list of alternatives as `` `a` `` or ``one of `a`, `b` ``.
+fn one_of(alternatives: &[String]) -> String {
+    let quoted = alternatives
+        .iter()
+        .map(|alternative| format!("`{alternative}`"))
+        .collect::<Vec<_>>();
+    match quoted.as_slice() {
+        [single] => single.clone(),
+        _ => format!("one of {}", quoted.join(", ")),
+    }
+}
@@ -0,0 +1,168 @@
+//! Enumeration of feature combinations for testing, in the style of `cargo-hack`.
+
+use crate::{Error, Manifest};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// How the combinations of a [Manifest::feature_matrix()] are built.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FeatureMatrixMode {
+    /// No features, and every feature on its own.
+    EachFeature,
+    /// All combinations of features.
+    #[default]
+    Powerset,
+}
+
+/// Options for [Manifest::feature_matrix()].
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct FeatureMatrixOptions {
+    pub mode: FeatureMatrixMode,
+    /// The maximum number of features (or groups) in a combination of the
+    /// [FeatureMatrixMode::Powerset] mode.
+    pub depth: Option<usize>,
+    /// Features that are never enabled.
+    pub exclude: Vec<String>,
+    /// Features that are always enabled together, and count as a single feature.
+    pub groups: Vec<Vec<String>>,
+    /// Also use the implicit features of optional dependencies.
+    pub optional_deps: bool,
+}
+
+impl<PackageMetadata, WorkspaceMetadata> Manifest<PackageMetadata, WorkspaceMetadata> {
+    /// Returns the combinations of features to test, each of which is meant to be
+    /// used with `--no-default-features --features ...`.
+    ///
+    /// The combinations are computed lazily, since a powerset grows exponentially
+    /// with the number of features. The first combination is always the empty one.
+    /// Combinations in which one feature already enables another one are skipped,
+    /// and so are combinations that enable the same features as an earlier one,
+    /// ignoring the `default` feature itself. The order is deterministic:
+    /// combinations are sorted by size, and then by the names of their features.
+    ///
+    /// Returns [Error::UnknownFeature] if an excluded or grouped feature is not defined.
+    pub fn feature_matrix(
+        &self,
+        options: &FeatureMatrixOptions,
+    ) -> Result<impl Iterator<Item = Vec<String>> + '_, Error> {
+        let feature_map = self.feature_map();
+        let explicit = self.features.clone().unwrap_or_default();
+        let known = |feature: &str| {
+            feature_map
+                .contains_key(feature)
+                .then_some(())
+                .ok_or_else(|| Error::UnknownFeature {
+                    feature: feature.to_string(),
+                })
+        };
+
+        let mut grouped = BTreeSet::new();
+        let mut units: Vec<BTreeSet<String>> = Vec::new();
+        for group in &options.groups {
+            for feature in group {
+                known(feature)?;
+                grouped.insert(feature.as_str());
+            }
+            units.push(group.iter().cloned().collect());
+        }
+        for feature in &options.exclude {
+            known(feature)?;
+        }
+        for feature in feature_map.keys() {
+            let is_candidate = options.optional_deps || explicit.contains_key(feature);
+            if is_candidate
+                && !grouped.contains(feature.as_str())
+                && !options.exclude.contains(feature)
+            {
+                units.push(BTreeSet::from([feature.clone()]));
+            }
+        }
+        units.sort();
+
+        // The features that each unit enables, to detect redundant combinations
+        let implied = units
+            .iter()
+            .map(|unit| {
+                let requested = unit.iter().map(String::as_str).collect::<Vec<_>>();
+                Ok(self.resolve_features(&requested, false)?.features)
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+
+        let max_size = match options.mode {
+            FeatureMatrixMode::EachFeature => 1,
+            FeatureMatrixMode::Powerset => options.depth.unwrap_or(units.len()),
+        };
+        let unit_count = units.len();
+        let combinations = (1..=max_size.min(unit_count))
+            .flat_map(move |size| Combinations::new(unit_count, size));
+
+        let mut seen = BTreeSet::from([ResolvedKey::default()]);
+        let matrix = combinations.filter_map(move |combination| {
+            let is_redundant = combination.iter().any(|&a| {
+                combination
+                    .iter()
+                    .any(|&b| a != b && units[b].is_subset(&implied[a]))
+            });
+            if is_redundant {
+                return None;
+            }
+
+            let features = combination
+                .iter()
+                .flat_map(|&i| units[i].iter().cloned())
+                .collect::<BTreeSet<_>>();
+            let requested = features.iter().map(String::as_str).collect::<Vec<_>>();
+            // Each unit resolved successfully, so any combination of them does too
+            let resolved = self
+                .resolve_features(&requested, false)
+                .expect("combinations of valid features resolve");
+            let mut enabled = resolved.features;
+            enabled.remove("default");
+            let key = (enabled, resolved.dependencies, resolved.dependency_features);
+            seen.insert(key).then(|| features.into_iter().collect())
+        });
+        Ok(std::iter::once(Vec::new()).chain(matrix))
+    }
+}
+
+/// The features, optional dependencies and dependency features that a combination enables.
+type ResolvedKey = (
+    BTreeSet<String>,
+    BTreeSet<String>,
+    BTreeMap<String, BTreeSet<String>>,
+);
+
+/// Yields all combinations of `size` indices in `0..n`, in lexicographic order.
+struct Combinations {
+    n: usize,
+    indices: Option<Vec<usize>>,
+}
+
+impl Combinations {
+    fn new(n: usize, size: usize) -> Self {
+        Self {
+            n,
+            indices: (size <= n).then(|| (0..size).collect()),
+        }
+    }
+}
+
+impl Iterator for Combinations {
+    type Item = Vec<usize>;
+
+    fn next(&mut self) -> Option<Vec<usize>> {
+        let indices = self.indices.as_mut()?;
+        let current = indices.clone();
+        let size = indices.len();
+        // Find the rightmost index that can still be incremented
+        match (0..size).rev().find(|&i| indices[i] < self.n - size + i) {
+            Some(i) => {
+                indices[i] += 1;
+                for j in i + 1..size {
+                    indices[j] = indices[j - 1] + 1;
+                }
+            }
+            None => self.indices = None,
+        }
+        Some(current)
+    }
+}
@@ -0,0 +1,530 @@
+//! Parsing and resolution of the `[features]` table
+//! (see <https://doc.rust-lang.org/cargo/reference/features.html>).
+
+use crate::{DependencyKind, Edition, Error, Manifest, MaybeInherited, Product, ProductKind};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+/// A single value in the list of a feature, e.g. `serde`, `dep:serde` or `serde?/std`.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum FeatureValue {
+    /// Another feature of this package, e.g. `std`.
+    ///
+    /// This may also be the implicit feature of an optional dependency.
+    Feature(String),
+    /// An optional dependency, e.g. `dep:serde`.
+    Dep { dep_name: String },
+    /// A feature of a dependency, e.g. `serde/std` or `serde?/std`.
+    ///
+    /// Unless `weak` is set, this also enables the dependency if it is optional.
+    DepFeature {
+        dep_name: String,
+        dep_feature: String,
+        weak: bool,
+    },
+}
+
+impl FeatureValue {
+    pub fn new(value: &str) -> Self {
+        if let Some(dep_name) = value.strip_prefix("dep:") {
+            return Self::Dep {
+                dep_name: dep_name.to_string(),
+            };
+        }
+        match value.split_once('/') {
+            Some((dep_name, dep_feature)) => {
+                let (dep_name, weak) = match dep_name.strip_suffix('?') {
+                    Some(dep_name) => (dep_name, true),
+                    None => (dep_name, false),
+                };
+                Self::DepFeature {
+                    dep_name: dep_name.to_string(),
+                    dep_feature: dep_feature.to_string(),
+                    weak,
+                }
+            }
+            None => Self::Feature(value.to_string()),
+        }
+    }
+}
+
+impl From<&str> for FeatureValue {
+    fn from(value: &str) -> Self {
+        Self::new(value)
+    }
+}
+
+impl fmt::Display for FeatureValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Feature(feature) => f.write_str(feature),
+            Self::Dep { dep_name } => write!(f, "dep:{dep_name}"),
+            Self::DepFeature {
+                dep_name,
+                dep_feature,
+                weak,
+            } => {
+                let weak = if *weak { "?" } else { "" };
+                write!(f, "{dep_name}{weak}/{dep_feature}")
+            }
+        }
+    }
+}
+
+/// A problem with the `[features]` table, see [Manifest::validate_features()], or with
+/// the `required-features` of a product, see [Manifest::validate_required_features()].
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum FeatureError {
+    #[error("invalid feature name `{feature}`: {reason}")]
+    InvalidName { feature: String, reason: String },
+    #[error(
+        "feature `{feature}` includes `{value}` which is neither a dependency nor another feature"
+    )]
+    UnknownFeature {
+        feature: String,
+        value: FeatureValue,
+    },
+    #[error("feature `{feature}` includes `{value}`, but `{dep_name}` is not a dependency")]
+    UnknownDependency {
+        feature: String,
+        value: FeatureValue,
+        dep_name: String,
+    },
+    #[error(
+        "feature `{feature}` includes `{value}`, but `{dep_name}` is not an optional dependency"
+    )]
+    NonOptionalDependency {
+        feature: String,
+        value: FeatureValue,
+        dep_name: String,
+    },
+    #[error("feature `{feature}` includes `{value}`, but `{dep_name}` is an optional dependency without an implicit feature, use `dep:{dep_name}` to enable it")]
+    NoImplicitFeature {
+        feature: String,
+        value: FeatureValue,
+        dep_name: String,
+    },
+    #[error("optional dependency `{dep_name}` is not enabled by any feature")]
+    UnusedOptionalDependency { dep_name: String },
+    #[error("invalid feature `{feature}` in required-features of {} `{product}`: {reason}", kind.table_name())]
+    InvalidRequiredFeature {
+        kind: ProductKind,
+        product: String,
+        feature: FeatureValue,
+        reason: String,
+    },
+}
+
+/// The features and optional dependencies that are enabled for a package,
+/// see [Manifest::resolve_features()].
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ResolvedFeatures {
+    /// The enabled features of the package, including implicit features.
+    pub features: BTreeSet<String>,
+    /// The enabled optional dependencies.
+    pub dependencies: BTreeSet<String>,
+    /// The features that are enabled on dependencies with `dep/feature`, for all
+    /// dependencies that are enabled.
+    pub dependency_features: BTreeMap<String, BTreeSet<String>>,
+}
+
+/// Whether a product is built with a set of features, see [Manifest::products_for_features()].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProductStatus<'a> {
+    pub kind: ProductKind,
+    pub product: &'a Product,
+    /// The required features of the product that are not enabled. Cargo skips the
+    /// product unless this is empty.
+    pub missing_features: Vec<&'a str>,
+}
+
+impl ProductStatus<'_> {
+    /// Returns `true` if Cargo would build the product.
+    pub fn is_built(&self) -> bool {
+        self.missing_features.is_empty()
+    }
+}
+
+impl<PackageMetadata, WorkspaceMetadata> Manifest<PackageMetadata, WorkspaceMetadata> {
+    /// Returns the parsed `[features]` table together with the implicit features that
+    /// Cargo creates for optional dependencies.
+    ///
+    /// Before the 2024 edition, every optional dependency that is never referenced
+    /// with `dep:` gets an implicit feature of the same name. An inherited edition
+    /// is treated as an edition before 2024, so call [Manifest::inherit_from()] first.
+    pub fn feature_map(&self) -> BTreeMap<String, Vec<FeatureValue>> {
+        let mut features = self.explicit_features();
+
+        let edition = self.package.as_ref().and_then(|p| p.edition.as_ref());
+        if matches!(edition, Some(MaybeInherited::Local(Edition::E2024))) {
+            return features;
+        }
+
+        let explicit = features
+            .values()
+            .flatten()
+            .filter_map(|value| match value {
+                FeatureValue::Dep { dep_name } => Some(dep_name.clone()),
+                _ => None,
+            })
+            .collect::<BTreeSet<_>>();
+        for name in self.optional_dependencies() {
+            if !explicit.contains(name) && !features.contains_key(name) {
+                let value = FeatureValue::Dep {
+                    dep_name: name.to_string(),
+                };
+                features.insert(name.to_string(), vec![value]);
+            }
+        }
+        features
+    }
+
+    /// Computes the transitive set of features and optional dependencies that are
+    /// enabled by the `requested` features, and the `default` feature if `default`
+    /// is set.
+    ///
+    /// Returns [Error::UnknownFeature] if a requested or referenced feature is not
+    /// defined. The `default` feature is always considered to be defined.
+    pub fn resolve_features(
+        &self,
+        requested: &[&str],
+        default: bool,
+    ) -> Result<ResolvedFeatures, Error> {
+        let feature_map = self.feature_map();
+        let optional = self.optional_dependencies();
+
+        let mut resolved = ResolvedFeatures::default();
+        let mut queue = requested
+            .iter()
+            .map(|feature| FeatureValue::Feature(feature.to_string()))
+            .collect::<Vec<_>>();
+        if default {
+            queue.push(FeatureValue::Feature("default".to_string()));
+        }
+
+        while let Some(value) = queue.pop() {
+            match value {
+                FeatureValue::Feature(feature) => {
+                    if resolved.features.contains(&feature) {
+                        continue;
+                    }
+                    match feature_map.get(&feature) {
+                        Some(values) => queue.extend(values.iter().cloned()),
+                        None if feature == "default" => continue,
+                        None => return Err(Error::UnknownFeature { feature }),
+                    }
+                    resolved.features.insert(feature);
+                }
+                FeatureValue::Dep { dep_name } => {
+                    resolved.dependencies.insert(dep_name);
+                }
+                FeatureValue::DepFeature {
+                    dep_name,
+                    dep_feature,
+                    weak,
+                } => {
+                    // `dep?/feature` doesn't enable the dependency itself
+                    if !weak && optional.contains(dep_name.as_str()) {
+                        // To retain compatibility, this also enables a feature of the same name
+                        if feature_map.contains_key(&dep_name) {
+                            queue.push(FeatureValue::Feature(dep_name.clone()));
+                        }
+                        resolved.dependencies.insert(dep_name.clone());
+                    }
+                    resolved
+                        .dependency_features
+                        .entry(dep_name)
+                        .or_default()
+                        .insert(dep_feature);
+                }
+            }
+        }
+
+        // Features of optional dependencies only apply if the dependency is enabled
+        resolved.dependency_features.retain(|dep, _| {
+            !optional.contains(dep.as_str()) || resolved.dependencies.contains(dep)
+        });
+        Ok(resolved)
+    }
+
+    /// Checks the `[features]` table against the dependencies of the package,
+    /// following the rules that Cargo enforces when loading a manifest.
+    ///
+    /// Returns all problems that were found, or an empty list if the features are valid.
+    pub fn validate_features(&self) -> Vec<FeatureError> {
+        let mut errors = Vec::new();
+        let features = self.explicit_features();
+        let feature_map = self.feature_map();
+        let optional = self.optional_dependencies();
+        let dependencies = self.dependency_names();
+
+        for (feature, values) in &features {
+            if let Err(reason) = validate_feature_name(feature) {
+                errors.push(FeatureError::InvalidName {
+                    feature: feature.clone(),
+                    reason: reason.to_string(),
+                });
+            }
+
+            for value in values {
+                let error = |dep_name: &str| (feature.clone(), value.clone(), dep_name.to_string());
+                match value {
+                    FeatureValue::Feature(name) => {
+                        if feature_map.contains_key(name) {
+                            continue;
+                        }
+                        let (feature, value, dep_name) = error(name);
+                        errors.push(if optional.contains(name.as_str()) {
+                            FeatureError::NoImplicitFeature {
+                                feature,
+                                value,
+                                dep_name,
+                            }
+                        } else if dependencies.contains(name.as_str()) {
+                            FeatureError::NonOptionalDependency {
+                                feature,
+                                value,
+                                dep_name,
+                            }
+                        } else {
+                            FeatureError::UnknownFeature { feature, value }
+                        });
+                    }
+                    FeatureValue::Dep { dep_name }
+                    | FeatureValue::DepFeature {
+                        dep_name,
+                        weak: true,
+                        ..
+                    } => {
+                        if optional.contains(dep_name.as_str()) {
+                            continue;
+                        }
+                        let (feature, value, dep_name) = error(dep_name);
+                        errors.push(if dependencies.contains(dep_name.as_str()) {
+                            FeatureError::NonOptionalDependency {
+                                feature,
+                                value,
+                                dep_name,
+                            }
+                        } else {
+                            FeatureError::UnknownDependency {
+                                feature,
+                                value,
+                                dep_name,
+                            }
+                        });
+                    }
+                    FeatureValue::DepFeature { dep_name, .. } => {
+                        if !dependencies.contains(dep_name.as_str()) {
+                            let (feature, value, dep_name) = error(dep_name);
+                            errors.push(FeatureError::UnknownDependency {
+                                feature,
+                                value,
+                                dep_name,
+                            });
+                        }
+                    }
+                }
+            }
+        }
+
+        let reachable = feature_map
+            .values()
+            .flatten()
+            .filter_map(|value| match value {
+                FeatureValue::Dep { dep_name }
+                | FeatureValue::DepFeature {
+                    dep_name,
+                    weak: false,
+                    ..
+                } => Some(dep_name.as_str()),
+                _ => None,
+            })
+            .collect::<BTreeSet<_>>();
+        for dep_name in optional.difference(&reachable) {
+            errors.push(FeatureError::UnusedOptionalDependency {
+                dep_name: dep_name.to_string(),
+            });
+        }
+
+        errors
+    }
+
+    /// Checks the `required-features` of all binaries, examples, tests and benchmarks.
+    ///
+    /// Each required feature must be a feature of the package (including implicit
+    /// features) or the name of an optional dependency. `dep/feature` is allowed if
+    /// `dep` is a dependency, but `dep:` and weak `dep?/feature` values are not.
+    ///
+    /// Returns all problems that were found, or an empty list if the requirements are valid.
+    pub fn validate_required_features(&self) -> Vec<FeatureError> {
+        let feature_map = self.feature_map();
+        let optional = self.optional_dependencies();
+        let dependencies = self.dependency_names();
+
+        let mut errors = Vec::new();
+        for (kind, index, product) in self.products_with_index() {
+            if kind == ProductKind::Lib {
+                continue;
+            }
+            for feature in &product.required_features {
+                let value = FeatureValue::new(feature);
+                let reason = match &value {
+                    FeatureValue::Feature(name) => {
+                        if feature_map.contains_key(name) || optional.contains(name.as_str()) {
+                            continue;
+                        }
+                        format!("`{name}` is not present in the [features] section")
+                    }
+                    FeatureValue::DepFeature {
+                        dep_name,
+                        weak: false,
+                        ..
+                    } => {
+                        if dependencies.contains(dep_name.as_str()) {
+                            continue;
+                        }
+                        format!("`{dep_name}` is not a dependency")
+                    }
+                    FeatureValue::Dep { .. } => {
+                        "`dep:` prefixed feature values are not allowed".to_string()
+                    }
+                    FeatureValue::DepFeature { weak: true, .. } => {
+                        "optional dependency features with `?` are not allowed".to_string()
+                    }
+                };
+                errors.push(FeatureError::InvalidRequiredFeature {
+                    kind,
+                    product: product_name(product, index),
+                    feature: value,
+                    reason,
+                });
+            }
+        }
+        errors
+    }
+
+    /// Returns all products of the package together with the `required-features`
+    /// that are missing from `features`, i.e. which products Cargo would build and
+    /// which it would skip.
+    ///
+    /// A required feature is enabled if it is in [ResolvedFeatures::features], names
+    /// an enabled optional dependency, or is a `dep/feature` that is enabled for `dep`.
+    /// The library is always built.
+    pub fn products_for_features(&self, features: &ResolvedFeatures) -> Vec<ProductStatus<'_>> {
+        let optional = self.optional_dependencies();
+        self.products_with_index()
+            .map(|(kind, _, product)| {
+                let missing_features = match kind {
+                    ProductKind::Lib => Vec::new(),
+                    _ => product
+                        .required_features
+                        .iter()
+                        .map(String::as_str)
+                        .filter(|feature| match FeatureValue::new(feature) {
+                            FeatureValue::Feature(name) => {
+                                !features.features.contains(&name)
+                                    && !features.dependencies.contains(&name)
+                            }
+                            FeatureValue::Dep { dep_name } => {
+                                !features.dependencies.contains(&dep_name)
+                            }
+                            FeatureValue::DepFeature {
+                                dep_name,
+                                dep_feature,
+                                ..
+                            } => {
+                                let enabled = !optional.contains(dep_name.as_str())
+                                    || features.dependencies.contains(&dep_name);
+                                !enabled
+                                    || !features
+                                        .dependency_features
+                                        .get(&dep_name)
+                                        .is_some_and(|f| f.contains(&dep_feature))
+                            }
+                        })
+                        .collect(),
+                };
+                ProductStatus {
+                    kind,
+                    product,
+                    missing_features,
+                }
+            })
+            .collect()
+    }
+
+    /// Returns the parsed `[features]` table, without implicit features.
+    fn explicit_features(&self) -> BTreeMap<String, Vec<FeatureValue>> {
+        self.features
+            .iter()
+            .flatten()
+            .map(|(name, values)| {
+                let values = values.iter().map(|value| FeatureValue::new(value));
+                (name.clone(), values.collect())
+            })
+            .collect()
+    }
+
+    /// Returns all products with their kind and their index in the table of that kind.
+    fn products_with_index(&self) -> impl Iterator<Item = (ProductKind, usize, &Product)> {
+        let lib = self.lib.iter().map(|lib| (ProductKind::Lib, 0, lib));
+        let tables = [
+            (ProductKind::Bin, &self.bin),
+            (ProductKind::Example, &self.example),
+            (ProductKind::Test, &self.test),
+            (ProductKind::Bench, &self.bench),
+        ];
+        lib.chain(tables.into_iter().flat_map(|(kind, products)| {
+            products
+                .iter()
+                .enumerate()
+                .map(move |(index, product)| (kind, index, product))
+        }))
+    }
+
+    /// Returns the names of all dependencies, including those of `[target]` tables.
+    fn dependency_names(&self) -> BTreeSet<&str> {
+        self.dependency_tables()
+            .into_iter()
+            .flat_map(|(_, _, deps)| deps.keys())
+            .map(String::as_str)
+            .collect()
+    }
+
+    /// Returns the names of all optional dependencies, including those of `[target]` tables.
+    pub(crate) fn optional_dependencies(&self) -> BTreeSet<&str> {
+        self.dependency_tables()
+            .into_iter()
+            .filter(|(_, kind, _)| *kind != DependencyKind::Development)
+            .flat_map(|(_, _, deps)| deps.iter())
+            .filter(|(_, dep)| dep.optional())
+            .map(|(name, _)| name.as_str())
+            .collect()
+    }
+}
+
+/// Returns the name of a product, or its path or index if the name is not set.
+fn product_name(product: &Product, index: usize) -> String {
+    match (&product.name, &product.path) {
+        (Some(name), _) => name.clone(),
+        (None, Some(path)) => path.clone(),
+        (None, None) => format!("#{index}"),
+    }
+}
+
+/// Checks a feature name against the rules of Cargo: it must start with a letter, a digit
+/// or `_`, and may only contain letters, digits, `_`, `-`, `+` and `.`.
+fn validate_feature_name(name: &str) -> Result<(), &'static str> {
+    let mut chars = name.chars();
+    let Some(first) = chars.next() else {
+        return Err("feature names must not be empty");
+    };
+    if !(first.is_alphanumeric() || first == '_') {
+        return Err("feature names must start with a letter, a digit or `_`");
+    }
+    if !chars.all(|c| c.is_alphanumeric() || matches!(c, '_' | '-' | '+' | '.')) {
+        return Err("feature names may only contain letters, digits, `_`, `-`, `+` and `.`");
+    }
+    Ok(())
+}
@@ -0,0 +1,378 @@
+//! Emulation of Cargo's workspace inheritance
+//! (see <https://doc.rust-lang.org/cargo/reference/workspaces.html#the-package-table>).
+
+use crate::{
+    dependency_table_key, quote_key, Dependency, DependencyDetail, DepsSet, EffectiveLint, Error,
+    InheritedDependencyDetail, LintsSet, Manifest, MaybeInherited, Package, StringOrBool,
+    Workspace, WorkspacePackage,
+};
+use std::path::Path;
+
+impl<PackageMetadata, WorkspaceMetadata> Manifest<PackageMetadata, WorkspaceMetadata> {
+    /// Replaces all `[package]` fields that are marked with `{key}.workspace = true`
+    /// with the values defined in the `[workspace.package]` table of the given workspace.
+    ///
+    /// This should be called before [Manifest::complete_from_abstract_filesystem()],
+    /// since target auto-discovery depends on the effective `edition` of the package.
+    ///
+    /// See [Package::inherit_from()] for the meaning of `workspace_root`.
+    pub fn inherit_from<Metadata>(
+        &mut self,
+        workspace: &Workspace<Metadata>,
+        workspace_root: &Path,
+    ) -> Result<(), Error> {
+        if let Some(package) = &mut self.package {
+            match &workspace.package {
+                Some(workspace_package) => {
+                    package.inherit_from(workspace_package, workspace_root)?
+                }
+                None => package.inherit_from(&WorkspacePackage::default(), workspace_root)?,
+            }
+        }
+        Ok(())
+    }
+
+    /// Replaces all dependencies that are defined as `{ workspace = true }` with
+    /// the corresponding entry of the `[workspace.dependencies]` table.
+    ///
+    /// This covers `[dependencies]`, `[dev-dependencies]`, `[build-dependencies]`
+    /// and all of their `[target.'...']` variants. The merge follows Cargo's rules:
+    ///
+    /// - `features` of the member are added to the `features` of the workspace,
+    /// - `optional` is always taken from the member,
+    /// - `default-features = true` of the member turns the default features back on,
+    ///   while `default-features = false` has no effect,
+    /// - any other keys of the member are carried over,
+    /// - `path` is rebased onto `workspace_root`, which is the directory of the
+    ///   workspace manifest, either absolute or relative to the member directory.
+    ///
+    /// Returns [Error::MissingWorkspaceKey] for the first inherited dependency that
+    /// the workspace does not define, and [Error::InvalidWorkspaceInheritance] if a
+    /// member also defines a key that only the workspace may define, like `version`,
+    /// `path` or `package`.
+    pub fn inherit_dependencies_from<Metadata>(
+        &mut self,
+        workspace: &Workspace<Metadata>,
+        workspace_root: &Path,
+    ) -> Result<(), Error> {
+        let empty = DepsSet::new();
+        let workspace_deps = workspace.dependencies.as_ref().unwrap_or(&empty);
+        for (target, kind, deps) in self.dependency_tables_mut() {
+            let table = dependency_table_key(target, kind);
+            inherit_dependencies(deps, workspace_deps, workspace_root, &table)?;
+        }
+        Ok(())
+    }
+
+    /// Returns the lints that apply to this package, taking `lints.workspace = true`
+    /// into account.
+    ///
+    /// The lints are sorted by [Lint::priority()](crate::Lint::priority) and then by
+    /// [EffectiveLint::qualified_name()], which is the order in which Cargo passes
+    /// them to the compiler.
+    ///
+    /// Like Cargo, this rejects manifests that inherit the lints from the workspace
+    /// and also define lints locally.
+    pub fn effective_lints<Metadata>(
+        &self,
+        workspace: &Workspace<Metadata>,
+    ) -> Result<Vec<EffectiveLint>, Error> {
+        let Some(lints) = &self.lints else {
+            return Ok(Vec::new());
+        };
+
+        let lints = if lints.is_inherited() {
+            if !lints.lints.is_empty() {
+                return Err(Error::InvalidWorkspaceInheritance {
+                    key: "lints".to_string(),
+                });
+            }
+            workspace
+                .lints
+                .as_ref()
+                .ok_or_else(|| Error::MissingWorkspaceKey {
+                    key: "lints".to_string(),
+                })?
+        } else {
+            &lints.lints
+        };
+
+        Ok(sorted_lints(lints))
+    }
+}
+
+fn sorted_lints(lints: &LintsSet) -> Vec<EffectiveLint> {
+    let mut lints = lints
+        .iter()
+        .flat_map(|(tool, lints)| {
+            lints.iter().map(move |(name, lint)| EffectiveLint {
+                tool: tool.clone(),
+                name: name.clone(),
+                level: lint.level(),
+                priority: lint.priority(),
+            })
+        })
+        .collect::<Vec<_>>();
+
+    lints.sort_by_cached_key(|lint| (lint.priority, lint.qualified_name()));
+    lints
+}
+
+fn inherit_dependencies(
+    deps: &mut DepsSet,
+    workspace_deps: &DepsSet,
+    workspace_root: &Path,
+    table: &str,
+) -> Result<(), Error> {
+    for (name, dep) in deps.iter_mut() {
+        let Dependency::Inherited(inherited) = dep else {
+            continue;
+        };
+
+        let detail = match workspace_deps.get(name) {
+            Some(Dependency::Simple(version)) => DependencyDetail {
+                version: Some(version.clone()),
+                ..Default::default()
+            },
+            Some(Dependency::Detailed(detail)) => detail.clone(),
+            Some(Dependency::Inherited(_)) | None => {
+                return Err(Error::MissingWorkspaceKey {
+                    key: format!("{table}.{}", quote_key(name)),
+                })
+            }
+        };
+
+        let merged = merge_dependency(detail, inherited, workspace_root).map_err(|key| {
+            Error::InvalidWorkspaceInheritance {
+                key: format!("{table}.{}.{key}", quote_key(name)),
+            }
+        })?;
+        *dep = Dependency::Detailed(merged);
+    }
+    Ok(())
+}
+
+/// Keys of a dependency that cannot be combined with `workspace = true`, since they
+/// select the package or its source, which only the workspace may define, or the
+/// artifacts of the package.
+const WORKSPACE_ONLY_KEYS: &[&str] = &[
+    "version",
+    "registry",
+    "registry-index",
+    "registry_index",
+    "path",
+    "base",
+    "git",
+    "branch",
+    "tag",
+    "rev",
+    "package",
+    "artifact",
+    "lib",
+    "target",
+];
+
+/// Returns the key that the member must not define next to `workspace = true`
+/// as the error.
+fn merge_dependency(
+    mut detail: DependencyDetail,
+    inherited: &InheritedDependencyDetail,
+    workspace_root: &Path,
+) -> Result<DependencyDetail, String> {
+    if let Some(key) = inherited
+        .extra
+        .keys()
+        .find(|key| WORKSPACE_ONLY_KEYS.contains(&key.as_str()))
+    {
+        return Err(key.clone());
+    }
+
+    if let Some(features) = &inherited.features {
+        let merged = detail.features.get_or_insert_with(Vec::new);
+        for feature in features {
+            if !merged.contains(feature) {
+                merged.push(feature.clone());
+            }
+        }
+    }
+
+    detail.optional = inherited.optional;
+    if inherited.public.is_some() {
+        detail.public = inherited.public;
+    }
+    // Like Cargo, the member can only turn the default features back on
+    if inherited.default_features == Some(true) {
+        detail.default_features = Some(true);
+    }
+    detail.extra.extend(inherited.extra.0.clone());
+
+    // Paths with a `base` are not relative to the workspace root
+    if let (Some(path), None) = (&detail.path, &detail.base) {
+        detail.path = Some(rebase(workspace_root, path));
+    }
+
+    Ok(detail)
+}
+
+impl<Metadata> Package<Metadata> {
+    /// Replaces all fields that are marked with `{key}.workspace = true` with
+    /// the values defined in the given `[workspace.package]` table.
+    ///
+    /// Like in Cargo, `readme` and `license-file` are rebased onto `workspace_root`,
+    /// which is the directory of the workspace manifest, either absolute or relative
+    /// to the package directory.
+    ///
+    /// Returns [Error::MissingWorkspaceKeys] with all inherited fields that the
+    /// workspace does not define, in which case the package is left unchanged.
+    pub fn inherit_from(
+        &mut self,
+        workspace: &WorkspacePackage,
+        workspace_root: &Path,
+    ) -> Result<(), Error> {
+        let readme = workspace.readme.as_ref().map(|readme| match readme {
+            StringOrBool::String(path) => StringOrBool::String(rebase(workspace_root, path)),
+            StringOrBool::Bool(enabled) => StringOrBool::Bool(*enabled),
+        });
+        let license_file = workspace
+            .license_file
+            .as_deref()
+            .map(|path| rebase(workspace_root, path));
+
+        // The first pass only looks for missing keys, so that nothing is replaced
+        // if any of them is missing
+        let mut missing = Vec::new();
+        for apply in [false, true] {
+            inherit(
+                &mut self.edition,
+                &workspace.edition,
+                "edition",
+                apply,
+                &mut missing,
+            );
+            inherit(
+                &mut self.version,
+                &workspace.version,
+                "version",
+                apply,
+                &mut missing,
+            );
+            inherit(
+                &mut self.authors,
+                &workspace.authors,
+                "authors",
+                apply,
+                &mut missing,
+            );
+            inherit(
+                &mut self.description,
+                &workspace.description,
+                "description",
+                apply,
+                &mut missing,
+            );
+            inherit(
+                &mut self.homepage,
+                &workspace.homepage,
+                "homepage",
+                apply,
+                &mut missing,
+            );
+            inherit(
+                &mut self.documentation,
+                &workspace.documentation,
+                "documentation",
+                apply,
+                &mut missing,
+            );
+            inherit(&mut self.readme, &readme, "readme", apply, &mut missing);
+            inherit(
+                &mut self.keywords,
+                &workspace.keywords,
+                "keywords",
+                apply,
+                &mut missing,
+            );
+            inherit(
+                &mut self.categories,
+                &workspace.categories,
+                "categories",
+                apply,
+                &mut missing,
+            );
+            inherit(
+                &mut self.license,
+                &workspace.license,
+                "license",
+                apply,
+                &mut missing,
+            );
+            inherit(
+                &mut self.license_file,
+                &license_file,
+                "license-file",
+                apply,
+                &mut missing,
+            );
+            inherit(
+                &mut self.repository,
+                &workspace.repository,
+                "repository",
+                apply,
+                &mut missing,
+            );
+            inherit(
+                &mut self.rust_version,
+                &workspace.rust_version,
+                "rust-version",
+                apply,
+                &mut missing,
+            );
+            inherit(
+                &mut self.exclude,
+                &workspace.exclude,
+                "exclude",
+                apply,
+                &mut missing,
+            );
+            inherit(
+                &mut self.include,
+                &workspace.include,
+                "include",
+                apply,
+                &mut missing,
+            );
+            inherit(
+                &mut self.publish,
+                &workspace.publish,
+                "publish",
+                apply,
+                &mut missing,
+            );
+
+            if !missing.is_empty() {
+                return Err(Error::MissingWorkspaceKeys { keys: missing });
+            }
+        }
+        Ok(())
+    }
+}
+
+fn inherit<T: Clone>(
+    field: &mut Option<MaybeInherited<T>>,
+    workspace_value: &Option<T>,
+    key: &str,
+    apply: bool,
+    missing: &mut Vec<String>,
+) {
+    if let Some(MaybeInherited::Inherited { .. }) = field {
+        match workspace_value {
+            Some(value) if apply => *field = Some(MaybeInherited::Local(value.clone())),
+            Some(_) => {}
+            None => missing.push(format!("package.{key}")),
+        }
+    }
+}
+
+fn rebase(workspace_root: &Path, path: &str) -> String {
+    workspace_root.join(path).to_string_lossy().into_owned()
+}
@@ -8,19 +8,47 @@ use std::fs;
 use std::io;
 use std::path::Path;
 
+#[cfg(feature = "semver")]
+pub use semver;
 pub use toml::Value;
 
 pub type DepsSet = BTreeMap<String, Dependency>;
 pub type TargetDepsSet = BTreeMap<String, Target>;
 pub type FeatureSet = BTreeMap<String, Vec<String>>;
 pub type PatchSet = BTreeMap<String, DepsSet>;
+pub type ReplaceSet = BTreeMap<String, Dependency>;
 pub type ToolLintsSet = BTreeMap<String, Lint>;
 pub type LintsSet = BTreeMap<String, ToolLintsSet>;
 
 mod afs;
+mod cfg;
+mod dependencies;
+mod document;
 mod error;
+mod feature_matrix;
+mod features;
+mod inheritance;
+mod location;
+mod package_id_spec;
+mod patch;
+mod profile;
+mod source;
+#[cfg(feature = "semver")]
+mod version;
+mod workspace;
 pub use crate::afs::*;
+pub use crate::cfg::{Cfg, CfgExpr, Platform, TargetPlatform};
+pub use crate::dependencies::ActiveDependency;
+pub use crate::document::ManifestDocument;
 pub use crate::error::Error;
+pub use crate::feature_matrix::{FeatureMatrixMode, FeatureMatrixOptions};
+pub use crate::features::{FeatureError, FeatureValue, ProductStatus, ResolvedFeatures};
+pub use crate::location::Location;
+pub use crate::package_id_spec::{PackageIdSpec, SourceKind};
+pub use crate::patch::{PatchEntry, PatchReport, PatchedDependency};
+pub use crate::profile::ResolvedProfile;
+pub use crate::source::{DependencySource, GitReference};
+pub use crate::workspace::*;
 use serde::de::{Error as _, Unexpected};
 use std::str::FromStr;
 
@@ -60,6 +88,10 @@ pub struct Manifest<PackageMetadata = Value, WorkspaceMetadata = Value> {
 
     #[serde(skip_serializing_if = "Option::is_none")]
     pub patch: Option<PatchSet>,
+    /// The deprecated predecessor of `[patch]`, keyed by the spec of the replaced package,
+    /// see [Manifest::replacements()].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub replace: Option<ReplaceSet>,
 
     /// Note that due to autolibs feature this is not the complete list
     /// unless you run `complete_from_path`
@@ -73,6 +105,13 @@ pub struct Manifest<PackageMetadata = Value, WorkspaceMetadata = Value> {
 
     #[serde(skip_serializing_if = "Option::is_none")]
     pub lints: Option<MaybeInheritedLintsSet>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hints: Option<Hints>,
+
+    /// Keys that are not known to this crate, kept so that they survive a round-trip.
+    #[serde(flatten)]
+    pub extra: ExtraKeys,
 }
 
 impl<PackageMetadata, WorkspaceMetadata> Default for Manifest<PackageMetadata, WorkspaceMetadata> {
@@ -88,14 +127,17 @@ impl<PackageMetadata, WorkspaceMetadata> Default for Manifest<PackageMetadata, W
             target: None,
             features: None,
             patch: None,
+            replace: None,
             lib: None,
             profile: None,
             lints: None,
             badges: None,
+            hints: None,
             bin: Default::default(),
             bench: Default::default(),
             test: Default::default(),
             example: Default::default(),
+            extra: ExtraKeys::default(),
         }
     }
 }
@@ -126,6 +168,10 @@ pub struct Workspace<Metadata = Value> {
 
     #[serde(skip_serializing_if = "Option::is_none")]
     pub lints: Option<LintsSet>,
+
+    /// Keys that are not known to this crate, kept so that they survive a round-trip.
+    #[serde(flatten)]
+    pub extra: ExtraKeys,
 }
 
 /// The workspace.package table is where you define keys that can be inherited by members of a
@@ -177,6 +223,10 @@ pub struct WorkspacePackage {
     /// e.g. "1.63.0"
     #[serde(rename = "rust-version")]
     pub rust_version: Option<String>,
+
+    /// Keys that are not known to this crate, kept so that they survive a round-trip.
+    #[serde(flatten)]
+    pub extra: ExtraKeys,
 }
 
 fn default_true() -> bool {
@@ -205,6 +255,16 @@ impl Manifest<Value> {
     pub fn from_path(cargo_toml_path: impl AsRef<Path>) -> Result<Self, Error> {
         Self::from_path_with_metadata(cargo_toml_path)
     }
+
+    /// Parse contents of a `Cargo.toml` file, and return the paths of all keys
+    /// that are not part of the manifest format.
+    ///
+    /// See [Manifest::from_slice_with_metadata_and_unknown_keys()].
+    pub fn from_slice_with_unknown_keys(
+        cargo_toml_content: &[u8],
+    ) -> Result<(Self, Vec<String>), Error> {
+        Self::from_slice_with_metadata_and_unknown_keys(cargo_toml_content)
+    }
 }
 
 impl FromStr for Manifest<Value> {
@@ -224,29 +284,81 @@ impl<Metadata: for<'a> Deserialize<'a>> Manifest<Metadata> {
     /// Parse `Cargo.toml`, and parse its `[package.metadata]` into a custom Serde-compatible type.
     ///
     /// It does not call `complete_from_path`, so may be missing implicit data.
+    ///
+    /// Errors carry a [Location] with the position and key path of the problem.
     pub fn from_slice_with_metadata(cargo_toml_content: &[u8]) -> Result<Self, Error> {
-        let mut manifest: Self = toml_from_slice(cargo_toml_content)?;
-        if manifest.package.is_none() && manifest.workspace.is_none() {
-            // Some old crates lack the `[package]` header
-
-            let val: Value = toml_from_slice(cargo_toml_content)?;
-            if let Some(project) = val.get("project") {
-                manifest.package = Some(project.clone().try_into()?);
-            } else {
-                manifest.package = Some(val.try_into()?);
+        Self::parse(cargo_toml_content)
+            .map(|(manifest, _)| manifest)
+            .map_err(|err| err.in_content(&String::from_utf8_lossy(cargo_toml_content)))
+    }
+
+    /// Like [Manifest::from_slice_with_metadata()], but also returns the paths of all
+    /// keys that are not part of the manifest format, e.g. `package.autobin`.
+    ///
+    /// These are the keys that Cargo warns about as `unused manifest key`,
+    /// see [Manifest::unknown_keys()].
+    pub fn from_slice_with_metadata_and_unknown_keys(
+        cargo_toml_content: &[u8],
+    ) -> Result<(Self, Vec<String>), Error> {
+        let (manifest, uses_project_table) = Self::parse(cargo_toml_content)
+            .map_err(|err| err.in_content(&String::from_utf8_lossy(cargo_toml_content)))?;
+        let mut unknown_keys = manifest.unknown_keys();
+        if uses_project_table {
+            for key in &mut unknown_keys {
+                if let Some(rest) = key.strip_prefix("package.") {
+                    *key = format!("project.{rest}");
+                }
             }
         }
-        Ok(manifest)
+        Ok((manifest, unknown_keys))
+    }
+
+    /// Parses the manifest, and returns whether it uses the legacy `[project]` table.
+    fn parse(cargo_toml_content: &[u8]) -> Result<(Self, bool), Error> {
+        let mut manifest: Self = match toml_from_slice(cargo_toml_content) {
+            Ok(manifest) => manifest,
+            Err(err) => return Err(invalid_setting(cargo_toml_content).unwrap_or(err)),
+        };
+        if manifest.package.is_some() || manifest.workspace.is_some() {
+            return Ok((manifest, false));
+        }
+
+        // Some old crates lack the `[package]` header
+        let val: Value = toml_from_slice(cargo_toml_content)?;
+        if let Some(project) = val.get("project") {
+            let package = project.clone().try_into();
+            manifest.package = Some(package.map_err(|err| Error::from(err).at_key("project"))?);
+            manifest.extra.remove("project");
+            Ok((manifest, true))
+        } else {
+            let mut package: Package<Metadata> = val.try_into()?;
+            // The package and the rest of the manifest share the top-level table,
+            // so a top-level key is only unknown if neither of them uses it.
+            manifest
+                .extra
+                .retain(|key, _| package.extra.contains_key(key));
+            package.extra.clear();
+            manifest.package = Some(package);
+            Ok((manifest, false))
+        }
     }
 
     /// Parse contents from `Cargo.toml` file on disk, with custom Serde-compatible metadata type.
     ///
     /// Calls `complete_from_path`
+    ///
+    /// Errors carry a [Location] with the file path, and the position and key path
+    /// of the problem, if known.
     pub fn from_path_with_metadata(cargo_toml_path: impl AsRef<Path>) -> Result<Self, Error> {
         let cargo_toml_path = cargo_toml_path.as_ref();
-        let cargo_toml_content = fs::read(cargo_toml_path)?;
-        let mut manifest = Self::from_slice_with_metadata(&cargo_toml_content)?;
-        manifest.complete_from_path(cargo_toml_path)?;
+        let cargo_toml_content = fs::read(cargo_toml_path)
+            .map_err(|err| Error::from(err).in_file(cargo_toml_path, None))?;
+        let content = String::from_utf8_lossy(&cargo_toml_content);
+        let mut manifest = Self::from_slice_with_metadata(&cargo_toml_content)
+            .map_err(|err| err.in_file(cargo_toml_path, Some(&content)))?;
+        manifest
+            .complete_from_path(cargo_toml_path)
+            .map_err(|err| err.in_file(cargo_toml_path, Some(&content)))?;
         Ok(manifest)
     }
 
@@ -268,15 +380,6 @@ impl<Metadata: for<'a> Deserialize<'a>> Manifest<Metadata> {
         &mut self,
         fs: &FS,
     ) -> Result<(), Error> {
-        enum ProductType {
-            #[allow(dead_code)]
-            Lib,
-            Bin,
-            Example,
-            Test,
-            Bench,
-        }
-
         let autobins = self.autobins();
         let autotests = self.autotests();
         let autoexamples = self.autoexamples();
@@ -306,9 +409,11 @@ impl<Metadata: for<'a> Deserialize<'a>> Manifest<Metadata> {
                     {
                         lib.path = Some(format!("src/{fallback_name}.rs"));
                     } else {
-                        let msg =
-                            "can't find library, rename file to `src/lib.rs` or specify lib.path";
-                        return Err(Error::Other(msg.to_string()));
+                        let mut expected_paths = vec!["src/lib.rs".to_string()];
+                        if package.uses_legacy_auto_discovery() {
+                            expected_paths.push(format!("src/{fallback_name}.rs"));
+                        }
+                        return Err(Error::MissingLibraryTarget { expected_paths }.at_key("lib"));
                     }
                 }
 
@@ -343,13 +448,13 @@ impl<Metadata: for<'a> Deserialize<'a>> Manifest<Metadata> {
                 })
             }
 
-            let fill_target_defaults = |targets: &mut Vec<Product>, kind: ProductType| {
+            let fill_target_defaults = |targets: &mut Vec<Product>, kind: ProductKind| {
                 for target in targets {
                     if target.edition.is_none() {
                         target.edition = edition;
                     }
 
-                    if matches!(kind, ProductType::Example) && target.crate_type.is_none() {
+                    if matches!(kind, ProductKind::Example) && target.crate_type.is_none() {
                         target.crate_type = Some(vec!["bin".to_string()]);
                     }
                 }
@@ -367,7 +472,7 @@ impl<Metadata: for<'a> Deserialize<'a>> Manifest<Metadata> {
             }
 
             process_discovered_targets(&mut self.bin, discovered_targets, autobins)?;
-            fill_target_defaults(&mut self.bin, ProductType::Bin);
+            fill_target_defaults(&mut self.bin, ProductKind::Bin);
 
             // For the 2015 edition, cargo defaults to using `src/main.rs` as
             // the `path`, if it exists, unless it is explicitly set or there
@@ -380,15 +485,15 @@ impl<Metadata: for<'a> Deserialize<'a>> Manifest<Metadata> {
 
             let discovered_targets = discover_targets(fs, "examples")?;
             process_discovered_targets(&mut self.example, discovered_targets, autoexamples)?;
-            fill_target_defaults(&mut self.example, ProductType::Example);
+            fill_target_defaults(&mut self.example, ProductKind::Example);
 
             let discovered_targets = discover_targets(fs, "tests")?;
             process_discovered_targets(&mut self.test, discovered_targets, autotests)?;
-            fill_target_defaults(&mut self.test, ProductType::Test);
+            fill_target_defaults(&mut self.test, ProductKind::Test);
 
             let discovered_targets = discover_targets(fs, "benches")?;
             process_discovered_targets(&mut self.bench, discovered_targets, autobenches)?;
-            fill_target_defaults(&mut self.bench, ProductType::Bench);
+            fill_target_defaults(&mut self.bench, ProductKind::Bench);
 
             if matches!(package.build, None | Some(StringOrBool::Bool(true)))
                 && fs.file_names_in(".")?.contains("build.rs")
@@ -434,6 +539,231 @@ impl<Metadata: for<'a> Deserialize<'a>> Manifest<Metadata> {
         let default_value = !pkg.uses_legacy_auto_discovery() || self.bench.is_empty();
         pkg.autobenches.unwrap_or(default_value)
     }
+
+    /// Returns the paths of all keys that are not part of the manifest format,
+    /// e.g. `package.autobin` or `dev-dependancies`.
+    ///
+    /// The keys are collected from the `extra` fields of the manifest tables,
+    /// which are written back when the manifest is serialized.
+    /// Path segments that are not bare keys are quoted like in TOML,
+    /// e.g. `target."cfg(unix)".dependencies.foo.optinal`.
+    pub fn unknown_keys(&self) -> Vec<String> {
+        let mut keys = Vec::new();
+        push_extra_keys(&mut keys, "", &self.extra);
+
+        if let Some(package) = &self.package {
+            push_extra_keys(&mut keys, "package.", &package.extra);
+        }
+
+        if let Some(workspace) = &self.workspace {
+            push_extra_keys(&mut keys, "workspace.", &workspace.extra);
+            if let Some(package) = &workspace.package {
+                push_extra_keys(&mut keys, "workspace.package.", &package.extra);
+            }
+            if let Some(deps) = &workspace.dependencies {
+                push_dependency_keys(&mut keys, "workspace.dependencies.", deps);
+            }
+            if let Some(lints) = &workspace.lints {
+                push_lint_keys(&mut keys, "workspace.lints.", lints);
+            }
+        }
+
+        for (name, target) in self.target.iter().flatten() {
+            push_extra_keys(
+                &mut keys,
+                &format!("target.{}.", quote_key(name)),
+                &target.extra,
+            );
+        }
+        for (target, kind, deps) in self.dependency_tables() {
+            let prefix = format!("{}.", dependency_table_key(target, kind));
+            push_dependency_keys(&mut keys, &prefix, deps);
+        }
+
+        if let Some(lib) = &self.lib {
+            push_extra_keys(&mut keys, "lib.", &lib.extra);
+        }
+        let products = [
+            (&self.bin, "bin"),
+            (&self.example, "example"),
+            (&self.test, "test"),
+            (&self.bench, "bench"),
+        ];
+        for (products, table) in products {
+            for (i, product) in products.iter().enumerate() {
+                push_extra_keys(&mut keys, &format!("{table}.{i}."), &product.extra);
+            }
+        }
+
+        for (registry, deps) in self.patch.iter().flatten() {
+            push_dependency_keys(&mut keys, &format!("patch.{}.", quote_key(registry)), deps);
+        }
+        if let Some(replace) = &self.replace {
+            push_dependency_keys(&mut keys, "replace.", replace);
+        }
+        if let Some(hints) = &self.hints {
+            push_extra_keys(&mut keys, "hints.", &hints.extra);
+        }
+
+        if let Some(profiles) = &self.profile {
+            let profiles = [
+                ("release", &profiles.release),
+                ("dev", &profiles.dev),
+                ("test", &profiles.test),
+                ("bench", &profiles.bench),
+                ("doc", &profiles.doc),
+            ]
+            .into_iter()
+            .filter_map(|(name, profile)| Some((name, profile.as_ref()?)))
+            .chain(profiles.custom.iter().map(|(name, p)| (name.as_str(), p)));
+            for (name, profile) in profiles {
+                push_profile_keys(&mut keys, &format!("profile.{}.", quote_key(name)), profile);
+            }
+        }
+
+        if let Some(badges) = &self.badges {
+            push_badge_keys(&mut keys, badges);
+        }
+
+        if let Some(lints) = &self.lints {
+            push_lint_keys(&mut keys, "lints.", &lints.lints);
+        }
+
+        keys
+    }
+}
+
+impl<PackageMetadata, WorkspaceMetadata> Manifest<PackageMetadata, WorkspaceMetadata> {
+    /// Returns all dependency tables, including those of `[target]` tables,
+    /// together with the key of their `[target]` table.
+    pub(crate) fn dependency_tables(&self) -> Vec<(Option<&str>, DependencyKind, &DepsSet)> {
+        let mut tables = Vec::new();
+        let top_level = [
+            (DependencyKind::Normal, &self.dependencies),
+            (DependencyKind::Development, &self.dev_dependencies),
+            (DependencyKind::Build, &self.build_dependencies),
+        ];
+        for (kind, deps) in top_level {
+            if let Some(deps) = deps {
+                tables.push((None, kind, deps));
+            }
+        }
+        for (key, target) in self.target.iter().flatten() {
+            let key = Some(key.as_str());
+            tables.push((key, DependencyKind::Normal, &target.dependencies));
+            tables.push((key, DependencyKind::Development, &target.dev_dependencies));
+            tables.push((key, DependencyKind::Build, &target.build_dependencies));
+        }
+        tables
+    }
+
+    /// Like [Manifest::dependency_tables()], but returns mutable tables.
+    pub(crate) fn dependency_tables_mut(
+        &mut self,
+    ) -> Vec<(Option<&str>, DependencyKind, &mut DepsSet)> {
+        let mut tables = Vec::new();
+        let top_level = [
+            (DependencyKind::Normal, &mut self.dependencies),
+            (DependencyKind::Development, &mut self.dev_dependencies),
+            (DependencyKind::Build, &mut self.build_dependencies),
+        ];
+        for (kind, deps) in top_level {
+            if let Some(deps) = deps {
+                tables.push((None, kind, deps));
+            }
+        }
+        for (key, target) in self.target.iter_mut().flatten() {
+            let key = Some(key.as_str());
+            tables.push((key, DependencyKind::Normal, &mut target.dependencies));
+            tables.push((
+                key,
+                DependencyKind::Development,
+                &mut target.dev_dependencies,
+            ));
+            tables.push((key, DependencyKind::Build, &mut target.build_dependencies));
+        }
+        tables
+    }
+}
+
+/// Returns the key path of a dependency table, e.g. `target."cfg(unix)".dependencies`.
+pub(crate) fn dependency_table_key(target: Option<&str>, kind: DependencyKind) -> String {
+    match target {
+        Some(target) => format!("target.{}.{}", quote_key(target), kind.table_name()),
+        None => kind.table_name().to_string(),
+    }
+}
+
+fn push_extra_keys(keys: &mut Vec<String>, prefix: &str, extra: &ExtraKeys) {
+    keys.extend(
+        extra
+            .keys()
+            .map(|key| format!("{prefix}{}", quote_key(key))),
+    );
+}
+
+fn push_profile_keys(keys: &mut Vec<String>, prefix: &str, profile: &Profile) {
+    push_extra_keys(keys, prefix, &profile.extra);
+    for (spec, profile) in &profile.package {
+        let prefix = format!("{prefix}package.{}.", quote_key(&spec.to_string()));
+        push_profile_keys(keys, &prefix, profile);
+    }
+    if let Some(build_override) = &profile.build_override {
+        push_profile_keys(keys, &format!("{prefix}build-override."), build_override);
+    }
+}
+
+fn push_dependency_keys(keys: &mut Vec<String>, prefix: &str, deps: &DepsSet) {
+    for (name, dep) in deps {
+        let extra = match dep {
+            Dependency::Simple(_) => continue,
+            Dependency::Inherited(inherited) => &inherited.extra,
+            Dependency::Detailed(detail) => &detail.extra,
+        };
+        push_extra_keys(keys, &format!("{prefix}{}.", quote_key(name)), extra);
+    }
+}
+
+fn push_badge_keys(keys: &mut Vec<String>, badges: &Badges) {
+    push_extra_keys(keys, "badges.", &badges.extra);
+    let known = [
+        ("appveyor", &badges.appveyor),
+        ("circle-ci", &badges.circle_ci),
+        ("gitlab", &badges.gitlab),
+        ("travis-ci", &badges.travis_ci),
+        ("codecov", &badges.codecov),
+        ("coveralls", &badges.coveralls),
+        (
+            "is-it-maintained-issue-resolution",
+            &badges.is_it_maintained_issue_resolution,
+        ),
+        (
+            "is-it-maintained-open-issues",
+            &badges.is_it_maintained_open_issues,
+        ),
+    ];
+    for (name, badge) in known {
+        if let Some(badge) = badge {
+            push_extra_keys(keys, &format!("badges.{name}."), &badge.extra);
+        }
+    }
+    push_extra_keys(keys, "badges.maintenance.", &badges.maintenance.extra);
+}
+
+fn push_lint_keys(keys: &mut Vec<String>, prefix: &str, lints: &LintsSet) {
+    for (tool, lints) in lints {
+        for (name, lint) in lints {
+            if let Lint::Config(config) = lint {
+                let prefix = format!("{prefix}{}.{}.", quote_key(tool), quote_key(name));
+                push_extra_keys(keys, &prefix, &config.extra);
+            }
+        }
+    }
+}
+
+/// Formats a segment of a key path, quoting it unless it is a bare key.
+fn quote_key(key: &str) -> String {
+    toml_edit::Key::new(key).display_repr().into_owned()
 }
 
 #[derive(Debug)]
@@ -592,44 +922,317 @@ impl TryFrom<Value> for StripSetting {
                 "none" => Self::None,
                 "debuginfo" => Self::Debuginfo,
                 "symbols" => Self::Symbols,
-                other => {
-                    return Err(Error::Other(format!(
-                        "'{other}' is not a valid value for 'strip'"
-                    )))
-                }
+                other => return Err(Error::InvalidStripSetting(other.to_string())),
             },
-            _ => {
-                return Err(Error::Other(
-                    "wrong data type for strip setting".to_string(),
-                ))
-            }
+            other => return Err(Error::InvalidStripSetting(value_to_string(&other))),
         })
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+/// The `opt-level` setting of a profile.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize)]
+#[serde(try_from = "toml::Value")]
+pub enum OptLevel {
+    /// 0
+    O0,
+    /// 1
+    O1,
+    /// 2
+    O2,
+    /// 3
+    O3,
+    /// "s", optimize for binary size
+    S,
+    /// "z", optimize for binary size, but also turn off loop vectorization
+    Z,
+}
+
+impl Serialize for OptLevel {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Self::O0 => serializer.serialize_i64(0),
+            Self::O1 => serializer.serialize_i64(1),
+            Self::O2 => serializer.serialize_i64(2),
+            Self::O3 => serializer.serialize_i64(3),
+            Self::S => serializer.serialize_str("s"),
+            Self::Z => serializer.serialize_str("z"),
+        }
+    }
+}
+
+impl TryFrom<Value> for OptLevel {
+    type Error = Error;
+
+    fn try_from(v: Value) -> Result<Self, Error> {
+        Ok(match v {
+            Value::Integer(0) => Self::O0,
+            Value::Integer(1) => Self::O1,
+            Value::Integer(2) => Self::O2,
+            Value::Integer(3) => Self::O3,
+            // Like Cargo, numeric levels must not be quoted
+            Value::String(s) => match s.as_str() {
+                "s" => Self::S,
+                "z" => Self::Z,
+                other => return Err(Error::InvalidOptLevel(other.to_string())),
+            },
+            other => return Err(Error::InvalidOptLevel(value_to_string(&other))),
+        })
+    }
+}
+
+/// The `debug` setting of a profile.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize)]
+#[serde(try_from = "toml::Value")]
+pub enum DebugInfo {
+    /// false, 0 or "none"
+    None,
+    /// "line-directives-only"
+    LineDirectivesOnly,
+    /// "line-tables-only"
+    LineTablesOnly,
+    /// 1 or "limited"
+    Limited,
+    /// true, 2 or "full"
+    Full,
+}
+
+impl Serialize for DebugInfo {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Self::None => serializer.serialize_bool(false),
+            Self::LineDirectivesOnly => serializer.serialize_str("line-directives-only"),
+            Self::LineTablesOnly => serializer.serialize_str("line-tables-only"),
+            Self::Limited => serializer.serialize_i64(1),
+            Self::Full => serializer.serialize_bool(true),
+        }
+    }
+}
+
+impl TryFrom<Value> for DebugInfo {
+    type Error = Error;
+
+    fn try_from(v: Value) -> Result<Self, Error> {
+        Ok(match v {
+            Value::Boolean(false) | Value::Integer(0) => Self::None,
+            Value::Integer(1) => Self::Limited,
+            Value::Boolean(true) | Value::Integer(2) => Self::Full,
+            Value::String(s) => match s.as_str() {
+                "none" => Self::None,
+                "line-directives-only" => Self::LineDirectivesOnly,
+                "line-tables-only" => Self::LineTablesOnly,
+                "limited" => Self::Limited,
+                "full" => Self::Full,
+                other => return Err(Error::InvalidDebugInfo(other.to_string())),
+            },
+            other => return Err(Error::InvalidDebugInfo(value_to_string(&other))),
+        })
+    }
+}
+
+/// The `lto` setting of a profile.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize)]
+#[serde(try_from = "toml::Value")]
+pub enum Lto {
+    /// false, only performs "thin local LTO" on the local crate
+    ThinLocal,
+    /// true or "fat"
+    Fat,
+    /// "thin"
+    Thin,
+    /// "off", disables LTO completely
+    Off,
+}
+
+impl Serialize for Lto {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Self::ThinLocal => serializer.serialize_bool(false),
+            Self::Fat => serializer.serialize_bool(true),
+            Self::Thin => serializer.serialize_str("thin"),
+            Self::Off => serializer.serialize_str("off"),
+        }
+    }
+}
+
+impl TryFrom<Value> for Lto {
+    type Error = Error;
+
+    fn try_from(v: Value) -> Result<Self, Error> {
+        Ok(match v {
+            Value::Boolean(false) => Self::ThinLocal,
+            Value::Boolean(true) => Self::Fat,
+            Value::String(s) => match s.as_str() {
+                "fat" => Self::Fat,
+                "thin" => Self::Thin,
+                "off" => Self::Off,
+                other => return Err(Error::InvalidLto(other.to_string())),
+            },
+            other => return Err(Error::InvalidLto(value_to_string(&other))),
+        })
+    }
+}
+
+/// A profile setting that remembers how it was written in the manifest.
+///
+/// Some settings can be written in several ways with the same meaning, e.g. `debug = 2`,
+/// `debug = true` and `debug = "full"`. These compare equal, but each one is serialized
+/// the way it was written.
+#[derive(Debug, Clone)]
+pub struct Verbatim<T> {
+    value: T,
+    raw: Value,
+}
+
+impl<T> Verbatim<T> {
+    /// Returns the value as it was written in the manifest.
+    pub fn raw(&self) -> &Value {
+        &self.raw
+    }
+}
+
+impl<T> std::ops::Deref for Verbatim<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+
+impl<T: Serialize> From<T> for Verbatim<T> {
+    fn from(value: T) -> Self {
+        let raw = Value::try_from(&value).expect("profile settings are valid TOML values");
+        Self { value, raw }
+    }
+}
+
+impl<T: PartialEq> PartialEq for Verbatim<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+    }
+}
+
+impl<T: Eq> Eq for Verbatim<T> {}
+
+impl<T> Serialize for Verbatim<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.raw.serialize(serializer)
+    }
+}
+
+impl<'de, T: TryFrom<Value, Error = Error>> Deserialize<'de> for Verbatim<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let raw = Value::deserialize(deserializer)?;
+        let value = T::try_from(raw.clone()).map_err(D::Error::custom)?;
+        Ok(Self { value, raw })
+    }
+}
+
+/// The `panic` setting of a profile.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum PanicStrategy {
+    Unwind,
+    Abort,
+}
+
+/// The `split-debuginfo` setting of a profile.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SplitDebuginfo {
+    Off,
+    Packed,
+    Unpacked,
+}
+
+/// Formats a TOML value the way it would be written in a manifest.
+fn value_to_string(value: &Value) -> String {
+    document::to_edit_value(value).to_string()
+}
+
+/// The key of a `[profile.<name>.package.<spec>]` override.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum ProfilePackageSpec {
+    /// `"*"`, applies to all dependencies, but not to workspace members.
+    All,
+    /// Applies to the packages that match the spec.
+    Spec(PackageIdSpec),
+}
+
+impl FromStr for ProfilePackageSpec {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Error> {
+        match s {
+            "*" => Ok(Self::All),
+            spec => spec.parse().map(Self::Spec),
+        }
+    }
+}
+
+impl TryFrom<String> for ProfilePackageSpec {
+    type Error = Error;
+
+    fn try_from(s: String) -> Result<Self, Error> {
+        s.parse()
+    }
+}
+
+impl From<ProfilePackageSpec> for String {
+    fn from(spec: ProfilePackageSpec) -> Self {
+        spec.to_string()
+    }
+}
+
+impl std::fmt::Display for ProfilePackageSpec {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::All => f.write_str("*"),
+            Self::Spec(spec) => spec.fmt(f),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
 #[serde(rename_all = "kebab-case")]
 pub struct Profile {
     #[serde(alias = "opt_level")]
-    pub opt_level: Option<Value>,
-    pub debug: Option<Value>,
+    pub opt_level: Option<OptLevel>,
+    pub debug: Option<Verbatim<DebugInfo>>,
     pub rpath: Option<bool>,
     pub inherits: Option<String>,
-    pub lto: Option<Value>,
+    pub lto: Option<Verbatim<Lto>>,
     #[serde(alias = "debug_assertions")]
     pub debug_assertions: Option<bool>,
     #[serde(alias = "codegen_units")]
     pub codegen_units: Option<u16>,
-    pub panic: Option<String>,
+    pub panic: Option<PanicStrategy>,
     pub incremental: Option<bool>,
     #[serde(alias = "overflow_checks")]
     pub overflow_checks: Option<bool>,
     pub strip: Option<StripSetting>,
-    #[serde(default)]
-    pub package: BTreeMap<String, Value>,
-    pub split_debuginfo: Option<String>,
-    /// profile overrides
-    pub build_override: Option<Value>,
+    /// Overrides for individual packages, e.g. `[profile.dev.package."*"]`.
+    ///
+    /// Use [Profile::for_package()] to get the settings that apply to a package.
+    ///
+    /// Like in Cargo, overrides can't set `inherits`, `panic`, `lto` or `rpath`, and
+    /// can't be nested.
+    #[serde(
+        default,
+        skip_serializing_if = "BTreeMap::is_empty",
+        deserialize_with = "profile::deserialize_package_overrides"
+    )]
+    pub package: BTreeMap<ProfilePackageSpec, Profile>,
+    pub split_debuginfo: Option<SplitDebuginfo>,
+    /// Overrides for build scripts, proc macros and their dependencies.
+    ///
+    /// The same restrictions as for [Profile::package] apply.
+    #[serde(default, deserialize_with = "profile::deserialize_build_override")]
+    pub build_override: Option<Box<Profile>>,
+
+    /// Keys that are not known to this crate, kept so that they survive a round-trip.
+    #[serde(flatten)]
+    pub extra: ExtraKeys,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -698,6 +1301,10 @@ pub struct Product {
     /// The available options are "dylib", "rlib", "staticlib", "cdylib", and "proc-macro".
     #[serde(skip_serializing_if = "Option::is_none", alias = "crate_type")]
     pub crate_type: Option<Vec<String>>,
+
+    /// Keys that are not known to this crate, kept so that they survive a round-trip.
+    #[serde(flatten)]
+    pub extra: ExtraKeys,
 }
 
 impl Default for Product {
@@ -715,6 +1322,58 @@ impl Default for Product {
             required_features: Vec::new(),
             crate_type: None,
             edition: Some(Edition::default()),
+            extra: ExtraKeys::default(),
+        }
+    }
+}
+
+/// The kind of a product, i.e. the table that it is declared in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ProductKind {
+    Lib,
+    Bin,
+    Example,
+    Test,
+    Bench,
+}
+
+impl ProductKind {
+    /// Returns the name of the corresponding table in `Cargo.toml`.
+    pub fn table_name(&self) -> &'static str {
+        match self {
+            Self::Lib => "lib",
+            Self::Bin => "bin",
+            Self::Example => "example",
+            Self::Test => "test",
+            Self::Bench => "bench",
+        }
+    }
+}
+
+/// The kind of a dependency table, i.e. `[dependencies]`, `[dev-dependencies]`
+/// or `[build-dependencies]`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum DependencyKind {
+    Normal,
+    Development,
+    Build,
+}
+
+impl DependencyKind {
+    /// Returns the name of the corresponding table in `Cargo.toml`.
+    pub fn table_name(&self) -> &'static str {
+        match self {
+            Self::Normal => "dependencies",
+            Self::Development => "dev-dependencies",
+            Self::Build => "build-dependencies",
+        }
+    }
+
+    fn legacy_table_name(&self) -> &'static str {
+        match self {
+            Self::Normal => "dependencies",
+            Self::Development => "dev_dependencies",
+            Self::Build => "build_dependencies",
         }
     }
 }
@@ -728,6 +1387,10 @@ pub struct Target {
     pub dev_dependencies: DepsSet,
     #[serde(default, alias = "build_dependencies")]
     pub build_dependencies: DepsSet,
+
+    /// Keys that are not known to this crate, kept so that they survive a round-trip.
+    #[serde(flatten)]
+    pub extra: ExtraKeys,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -755,6 +1418,10 @@ impl Dependency {
         }
     }
 
+    /// Returns the version requirement of this dependency, or `"*"` if it has none.
+    ///
+    /// Inherited dependencies also return `"*"`, use
+    /// [Manifest::inherit_dependencies_from()] to resolve them first.
     pub fn req(&self) -> &str {
         match *self {
             Dependency::Simple(ref v) => v,
@@ -796,20 +1463,7 @@ impl Dependency {
     // `true` if it's an usual crates.io dependency,
     // `false` if git/path/alternative registry
     pub fn is_crates_io(&self) -> bool {
-        match *self {
-            Dependency::Simple(_) => true,
-            Dependency::Detailed(ref d) => {
-                // TODO: allow registry to be set to crates.io explicitly?
-                d.path.is_none()
-                    && d.registry.is_none()
-                    && d.registry_index.is_none()
-                    && d.git.is_none()
-                    && d.tag.is_none()
-                    && d.branch.is_none()
-                    && d.rev.is_none()
-            }
-            Dependency::Inherited(_) => false,
-        }
+        matches!(self.source(), Ok(DependencySource::CratesIo))
     }
 }
 
@@ -843,6 +1497,32 @@ pub struct DependencyDetail {
     pub default_features: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub package: Option<String>,
+    /// The name of a `[path-bases]` entry that `path` is relative to.
+    ///
+    /// Requires `cargo-features = ["path-bases"]`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub base: Option<String>,
+    /// Whether the dependency is exposed in the public API of this package.
+    ///
+    /// Requires `cargo-features = ["public-dependency"]`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub public: Option<bool>,
+    /// The artifacts of the dependency that this package depends on, e.g. its binaries.
+    ///
+    /// Requires `cargo-features = ["bindeps"]`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub artifact: Option<ArtifactKinds>,
+    /// Whether the library of an artifact dependency can be used as well.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lib: Option<bool>,
+    /// The platform that an artifact dependency is built for, or `"target"` for
+    /// the platform of the package itself.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target: Option<String>,
+
+    /// Keys that are not known to this crate, kept so that they survive a round-trip.
+    #[serde(flatten)]
+    pub extra: ExtraKeys,
 }
 
 impl DependencyDetail {
@@ -860,6 +1540,12 @@ impl DependencyDetail {
             optional,
             default_features,
             package,
+            base,
+            public,
+            artifact,
+            lib,
+            target,
+            extra,
         } = &self;
 
         if registry.is_some()
@@ -873,6 +1559,12 @@ impl DependencyDetail {
             || optional.is_some()
             || default_features.is_some()
             || package.is_some()
+            || base.is_some()
+            || public.is_some()
+            || artifact.is_some()
+            || lib.is_some()
+            || target.is_some()
+            || !extra.is_empty()
         {
             return Dependency::Detailed(self);
         }
@@ -884,6 +1576,89 @@ impl DependencyDetail {
     }
 }
 
+/// A kind of artifact of an artifact dependency, e.g. `bin` or `bin:name`.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum ArtifactKind {
+    /// `bin`, all binaries of the package.
+    AllBinaries,
+    /// `bin:<name>`, a single binary of the package.
+    SelectedBinary(String),
+    Cdylib,
+    Staticlib,
+}
+
+impl FromStr for ArtifactKind {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Error> {
+        match s {
+            "bin" => Ok(Self::AllBinaries),
+            "cdylib" => Ok(Self::Cdylib),
+            "staticlib" => Ok(Self::Staticlib),
+            _ => match s.strip_prefix("bin:") {
+                Some(name) if !name.is_empty() => Ok(Self::SelectedBinary(name.to_string())),
+                _ => Err(Error::InvalidArtifactKind(s.to_string())),
+            },
+        }
+    }
+}
+
+impl TryFrom<String> for ArtifactKind {
+    type Error = Error;
+
+    fn try_from(s: String) -> Result<Self, Error> {
+        s.parse()
+    }
+}
+
+impl From<ArtifactKind> for String {
+    fn from(kind: ArtifactKind) -> Self {
+        kind.to_string()
+    }
+}
+
+impl std::fmt::Display for ArtifactKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::AllBinaries => f.write_str("bin"),
+            Self::SelectedBinary(name) => write!(f, "bin:{name}"),
+            Self::Cdylib => f.write_str("cdylib"),
+            Self::Staticlib => f.write_str("staticlib"),
+        }
+    }
+}
+
+/// The `artifact` key of a dependency, which is either a single [ArtifactKind]
+/// or a list of them.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "StringOrVec", into = "StringOrVec")]
+pub struct ArtifactKinds(pub Vec<ArtifactKind>);
+
+impl TryFrom<StringOrVec> for ArtifactKinds {
+    type Error = Error;
+
+    fn try_from(value: StringOrVec) -> Result<Self, Error> {
+        let kinds = match value {
+            StringOrVec::String(kind) => vec![kind.parse()?],
+            StringOrVec::Vec(kinds) => kinds
+                .iter()
+                .map(|kind| kind.parse())
+                .collect::<Result<_, _>>()?,
+        };
+        Ok(Self(kinds))
+    }
+}
+
+impl From<ArtifactKinds> for StringOrVec {
+    fn from(kinds: ArtifactKinds) -> Self {
+        match <[ArtifactKind; 1]>::try_from(kinds.0) {
+            Ok([kind]) => StringOrVec::String(kind.to_string()),
+            Err(kinds) => StringOrVec::Vec(kinds.iter().map(ToString::to_string).collect()),
+        }
+    }
+}
+
 /// When a dependency is defined as `{ workspace = true }`,
 /// and workspace data hasn't been applied yet.
 #[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
@@ -896,6 +1671,17 @@ pub struct InheritedDependencyDetail {
 
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub optional: Option<bool>,
+
+    #[serde(default, alias = "default_features")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default_features: Option<bool>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub public: Option<bool>,
+
+    /// Keys that are not known to this crate, kept so that they survive a round-trip.
+    #[serde(flatten)]
+    pub extra: ExtraKeys,
 }
 
 /// Used as a wrapper for properties that may be inherited by workspace-level settings.
@@ -962,6 +1748,67 @@ impl<'de> Deserialize<'de> for True {
     }
 }
 
+/// Keys of a manifest table that are not known to this crate.
+///
+/// Unlike a plain map of [Value]s, this implements [Eq] by comparing floats
+/// by their bits, so that the manifest types containing it can implement [Eq] too.
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct ExtraKeys(pub BTreeMap<String, Value>);
+
+impl std::fmt::Debug for ExtraKeys {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl std::ops::Deref for ExtraKeys {
+    type Target = BTreeMap<String, Value>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl std::ops::DerefMut for ExtraKeys {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl From<BTreeMap<String, Value>> for ExtraKeys {
+    fn from(keys: BTreeMap<String, Value>) -> Self {
+        Self(keys)
+    }
+}
+
+impl PartialEq for ExtraKeys {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len()
+            && self
+                .iter()
+                .zip(other.iter())
+                .all(|((k1, v1), (k2, v2))| k1 == k2 && values_eq(v1, v2))
+    }
+}
+
+impl Eq for ExtraKeys {}
+
+fn values_eq(a: &Value, b: &Value) -> bool {
+    match (a, b) {
+        (Value::Float(a), Value::Float(b)) => a.to_bits() == b.to_bits(),
+        (Value::Array(a), Value::Array(b)) => {
+            a.len() == b.len() && a.iter().zip(b).all(|(a, b)| values_eq(a, b))
+        }
+        (Value::Table(a), Value::Table(b)) => {
+            a.len() == b.len()
+                && a.iter()
+                    .all(|(key, a)| b.get(key).is_some_and(|b| values_eq(a, b)))
+        }
+        (a, b) => a == b,
+    }
+}
+
 /// You can replace `Metadata` type with your own
 /// to parse into something more useful than a generic toml `Value`
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -1052,6 +1899,28 @@ pub struct Package<Metadata = Value> {
     pub publish: Option<MaybeInherited<Publish>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub resolver: Option<Resolver>,
+    /// Packages whose build scripts are run as the build script of this package.
+    ///
+    /// Requires `cargo-features = ["metabuild"]`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metabuild: Option<StringOrVec>,
+    /// Requires `cargo-features = ["test-dummy-unstable"]`, only used in Cargo's own tests.
+    #[serde(rename = "im-a-teapot", skip_serializing_if = "Option::is_none")]
+    pub im_a_teapot: Option<bool>,
+    /// The target that the package is always built for, e.g. `wasm32-unknown-unknown`.
+    ///
+    /// Requires `cargo-features = ["per-package-target"]`.
+    #[serde(rename = "forced-target", skip_serializing_if = "Option::is_none")]
+    pub forced_target: Option<String>,
+    /// The target that the package is built for if no `--target` is passed.
+    ///
+    /// Requires `cargo-features = ["per-package-target"]`.
+    #[serde(rename = "default-target", skip_serializing_if = "Option::is_none")]
+    pub default_target: Option<String>,
+
+    /// Keys that are not known to this crate, kept so that they survive a round-trip.
+    #[serde(flatten)]
+    pub extra: ExtraKeys,
 }
 
 impl<Metadata> Package<Metadata> {
@@ -1085,6 +1954,11 @@ impl<Metadata> Package<Metadata> {
             autobenches: None,
             publish: None,
             resolver: None,
+            metabuild: None,
+            im_a_teapot: None,
+            forced_target: None,
+            default_target: None,
+            extra: ExtraKeys::default(),
         }
     }
 
@@ -1127,6 +2001,13 @@ pub enum StringOrBool {
     Bool(bool),
 }
 
+#[derive(Clone, Debug, Deserialize, Serialize, Eq, PartialEq)]
+#[serde(untagged)]
+pub enum StringOrVec {
+    String(String),
+    Vec(Vec<String>),
+}
+
 #[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
 #[serde(untagged)]
 pub enum Publish {
@@ -1155,6 +2036,21 @@ impl PartialEq<bool> for Publish {
     }
 }
 
+/// The `[hints]` table, which contains suggestions for how to build the package
+/// that are ignored by older Cargo versions.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Hints {
+    /// Whether most of the items of the package are unused by its dependents,
+    /// which allows Cargo to defer their code generation.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mostly_unused: Option<bool>,
+
+    /// Keys that are not known to this crate, kept so that they survive a round-trip.
+    #[serde(flatten)]
+    pub extra: ExtraKeys,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "kebab-case")]
 pub struct Badge {
@@ -1165,6 +2061,10 @@ pub struct Badge {
     pub id: Option<String>,
     #[serde(alias = "project_name")]
     pub project_name: Option<String>,
+
+    /// Keys that are not known to this crate, kept so that they survive a round-trip.
+    #[serde(flatten)]
+    pub extra: ExtraKeys,
 }
 
 fn default_master() -> String {
@@ -1180,6 +2080,83 @@ where
     Ok(Deserialize::deserialize(deserializer).unwrap_or_default())
 }
 
+/// Returns the error of the first profile setting or dependency `artifact` that does
+/// not have a valid value, so that it is reported with its own [Error] variant
+/// rather than as a parse error.
+fn invalid_setting(cargo_toml_content: &[u8]) -> Option<Error> {
+    let manifest: Value = toml_from_slice(cargo_toml_content).ok()?;
+
+    for (name, profile) in table_entries(manifest.get("profile")) {
+        let key = format!("profile.{}", quote_key(name));
+        if let Err(err) = validate_profile_settings(profile, &key) {
+            return Some(err);
+        }
+    }
+
+    let mut dependency_tables = Vec::new();
+    for table in DEPENDENCY_TABLE_KEYS {
+        dependency_tables.push((table.to_string(), manifest.get(table)));
+    }
+    for (name, target) in table_entries(manifest.get("target")) {
+        for table in DEPENDENCY_TABLE_KEYS {
+            let key = format!("target.{}.{table}", quote_key(name));
+            dependency_tables.push((key, target.get(table)));
+        }
+    }
+    for (table, deps) in dependency_tables {
+        for (name, dep) in table_entries(deps) {
+            let Some(artifact) = dep.get("artifact") else {
+                continue;
+            };
+            let Ok(kinds) = artifact.clone().try_into::<StringOrVec>() else {
+                continue;
+            };
+            if let Err(err) = ArtifactKinds::try_from(kinds) {
+                return Some(err.at_key(&format!("{table}.{}.artifact", quote_key(name))));
+            }
+        }
+    }
+    None
+}
+
+const DEPENDENCY_TABLE_KEYS: [&str; 5] = [
+    "dependencies",
+    "dev-dependencies",
+    "dev_dependencies",
+    "build-dependencies",
+    "build_dependencies",
+];
+
+fn validate_profile_settings(profile: &Value, key: &str) -> Result<(), Error> {
+    for (setting, value) in table_entries(Some(profile)) {
+        let setting_key = format!("{key}.{}", quote_key(setting));
+        let result = match setting.as_str() {
+            "strip" => StripSetting::try_from(value.clone()).map(drop),
+            "opt-level" => OptLevel::try_from(value.clone()).map(drop),
+            "debug" => DebugInfo::try_from(value.clone()).map(drop),
+            "lto" => Lto::try_from(value.clone()).map(drop),
+            "build-override" => {
+                validate_profile_settings(value, &setting_key)?;
+                continue;
+            }
+            "package" => {
+                for (spec, profile) in table_entries(Some(value)) {
+                    let key = format!("{setting_key}.{}", quote_key(spec));
+                    validate_profile_settings(profile, &key)?;
+                }
+                continue;
+            }
+            _ => continue,
+        };
+        result.map_err(|err| err.at_key(&setting_key))?;
+    }
+    Ok(())
+}
+
+fn table_entries(value: Option<&Value>) -> impl Iterator<Item = (&String, &Value)> {
+    value.and_then(Value::as_table).into_iter().flatten()
+}
+
 fn toml_from_slice<T>(s: &'_ [u8]) -> Result<T, Error>
 where
     T: serde::de::DeserializeOwned,
@@ -1235,11 +2212,19 @@ pub struct Badges {
     /// `deprecated`, and the default `none`, which displays no badge on crates.io.
     #[serde(default, deserialize_with = "ok_or_default")]
     pub maintenance: Maintenance,
+
+    /// Badges that are not known to this crate, kept so that they survive a round-trip.
+    #[serde(flatten)]
+    pub extra: ExtraKeys,
 }
 
-#[derive(Debug, PartialEq, Eq, Copy, Clone, Default, Serialize, Deserialize)]
+#[derive(Debug, PartialEq, Eq, Clone, Default, Serialize, Deserialize)]
 pub struct Maintenance {
     pub status: MaintenanceStatus,
+
+    /// Keys that are not known to this crate, kept so that they survive a round-trip.
+    #[serde(flatten)]
+    pub extra: ExtraKeys,
 }
 
 #[derive(Debug, PartialEq, Eq, Copy, Clone, Hash, Serialize, Deserialize)]
@@ -1317,6 +2302,14 @@ pub struct LintConfig {
     pub level: LintLevel,
     #[serde(default)]
     pub priority: i8,
+    /// Expected `cfg` names and values of the `unexpected_cfgs` lint,
+    /// e.g. `["cfg(has_foo)"]`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub check_cfg: Option<Vec<String>>,
+
+    /// Keys that are not known to this crate, kept so that they survive a round-trip.
+    #[serde(flatten)]
+    pub extra: ExtraKeys,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -1356,6 +2349,28 @@ impl MaybeInheritedLintsSet {
     }
 }
 
+/// A single lint that applies to a package, see [Manifest::effective_lints()].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EffectiveLint {
+    /// The tool that the lint belongs to, e.g. `rust` or `clippy`.
+    pub tool: String,
+    pub name: String,
+    pub level: LintLevel,
+    pub priority: i8,
+}
+
+impl EffectiveLint {
+    /// Returns the lint name as it is passed to the compiler,
+    /// e.g. `unsafe_code` or `clippy::pedantic`.
+    pub fn qualified_name(&self) -> String {
+        if self.tool == "rust" {
+            self.name.clone()
+        } else {
+            format!("{}::{}", self.tool, self.name)
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
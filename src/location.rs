@@ -0,0 +1,248 @@
+use crate::Error;
+use std::fmt;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use toml_edit::{ImDocument, Item, Key, Table, TableLike, Value};
+
+/// Describes where in a `Cargo.toml` file an [Error] occurred.
+///
+/// Every field is optional, since not every error can be traced back to a
+/// specific file, key or position.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Location {
+    /// The path of the manifest file.
+    pub path: Option<PathBuf>,
+    /// The path of the offending key, e.g. `profile.release.strip` or `bin.0.name`,
+    /// in the same format as [Manifest::unknown_keys()](crate::Manifest::unknown_keys).
+    pub key: Option<String>,
+    /// The byte range of the offending key or value in the manifest.
+    pub span: Option<Range<usize>>,
+    /// The 1-based line number of the start of `span`.
+    pub line: Option<usize>,
+    /// The 1-based column number (in characters) of the start of `span`.
+    pub column: Option<usize>,
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut position = Vec::new();
+        if let Some(path) = &self.path {
+            position.push(path.display().to_string());
+        }
+        if let Some(line) = self.line {
+            position.push(line.to_string());
+        }
+        if let Some(column) = self.column {
+            position.push(column.to_string());
+        }
+        f.write_str(&position.join(":"))?;
+
+        if let Some(key) = &self.key {
+            if !position.is_empty() {
+                f.write_str(" ")?;
+            }
+            write!(f, "(`{key}`)")?;
+        }
+        Ok(())
+    }
+}
+
+impl Location {
+    fn set_key_span(&mut self, content: &str) {
+        if let (None, Some(key)) = (&self.span, &self.key) {
+            if let Some(span) = span_of_key(content, key) {
+                self.set_span(content, span);
+            }
+        }
+    }
+
+    fn set_span(&mut self, content: &str, span: Range<usize>) {
+        let before = &content.as_bytes()[..span.start.min(content.len())];
+        let line_start = before
+            .iter()
+            .rposition(|b| *b == b'\n')
+            .map_or(0, |i| i + 1);
+        self.line = Some(before.iter().filter(|b| **b == b'\n').count() + 1);
+        self.column = Some(
+            String::from_utf8_lossy(&before[line_start..])
+                .chars()
+                .count()
+                + 1,
+        );
+        self.span = Some(span);
+    }
+}
+
+impl Error {
+    /// Returns where in the manifest this error occurred, if known.
+    pub fn location(&self) -> Option<&Location> {
+        match self {
+            Error::Located { location, .. } => Some(location),
+            _ => None,
+        }
+    }
+
+    /// Returns this error without its [Location].
+    ///
+    /// This is useful to match on the kind of error.
+    pub fn without_location(&self) -> &Error {
+        match self {
+            Error::Located { source, .. } => source,
+            err => err,
+        }
+    }
+
+    fn map_location(self, f: impl FnOnce(&mut Location)) -> Error {
+        let (mut location, source) = match self {
+            Error::Located { location, source } => (location, source),
+            err => (Box::default(), Box::new(err)),
+        };
+        f(&mut location);
+        Error::Located { location, source }
+    }
+
+    /// Attaches the path of the offending key to this error.
+    pub(crate) fn at_key(self, key: &str) -> Error {
+        self.map_location(|location| location.key = Some(key.to_string()))
+    }
+
+    /// Attaches the file path to this error, and uses the manifest contents
+    /// to determine the position of the offending key if it is known.
+    pub(crate) fn in_file(self, path: &Path, content: Option<&str>) -> Error {
+        self.map_location(|location| {
+            location.path = Some(path.to_path_buf());
+            if let Some(content) = content {
+                location.set_key_span(content);
+            }
+        })
+    }
+
+    /// Attaches the path of the offending key to this error, and uses the
+    /// manifest contents to determine its position if the key exists.
+    pub(crate) fn at_key_in(self, key: &str, content: &str) -> Error {
+        self.map_location(|location| {
+            location.key = Some(key.to_string());
+            location.set_key_span(content);
+        })
+    }
+
+    /// Attaches the position and key path of a parse error in the given manifest contents,
+    /// or the position of the key of an error that already has one.
+    pub(crate) fn in_content(self, content: &str) -> Error {
+        let span = match self.without_location() {
+            Error::Parse(err) => err.span(),
+            Error::Edit(err) => err.span(),
+            Error::Utf8(err) => Some(err.valid_up_to()..err.valid_up_to()),
+            _ => None,
+        };
+        let Some(span) = span else {
+            // Errors that are not reported by the parser only know their key
+            return match self {
+                Error::Located { .. } => {
+                    self.map_location(|location| location.set_key_span(content))
+                }
+                err => err,
+            };
+        };
+
+        self.map_location(|location| {
+            if location.key.is_none() {
+                location.key = key_at(content, span.start);
+            }
+            location.set_span(content, span);
+        })
+    }
+}
+
+/// Returns the path of the innermost key whose key or value contains `offset`.
+fn key_at(content: &str, offset: usize) -> Option<String> {
+    let document = ImDocument::parse(content).ok()?;
+    let mut path = Vec::new();
+    find_in_table(document.as_table(), offset, &mut path).then(|| path.join("."))
+}
+
+fn find_in_table(table: &Table, offset: usize, path: &mut Vec<String>) -> bool {
+    for (key, item) in table.iter() {
+        let key = table.key(key).expect("key is in table");
+        path.push(key.display_repr().into_owned());
+        if find_in_item(key, item, offset, path) {
+            return true;
+        }
+        path.pop();
+    }
+    contains(table.span(), offset)
+}
+
+fn find_in_item(key: &Key, item: &Item, offset: usize, path: &mut Vec<String>) -> bool {
+    match item {
+        Item::Table(table) => find_in_table(table, offset, path),
+        Item::ArrayOfTables(array) => array.iter().enumerate().any(|(i, table)| {
+            path.push(i.to_string());
+            let found = find_in_table(table, offset, path);
+            if !found {
+                path.pop();
+            }
+            found
+        }),
+        Item::Value(value) => {
+            let span = key.span().zip(value.span()).map(|(k, v)| k.start..v.end);
+            contains(span, offset) && {
+                find_in_value(value, offset, path);
+                true
+            }
+        }
+        Item::None => false,
+    }
+}
+
+fn find_in_value(value: &Value, offset: usize, path: &mut Vec<String>) {
+    match value {
+        Value::InlineTable(table) => {
+            for (key, _) in table.iter() {
+                let (key, item) = table.get_key_value(key).expect("key is in table");
+                path.push(key.display_repr().into_owned());
+                if find_in_item(key, item, offset, path) {
+                    return;
+                }
+                path.pop();
+            }
+        }
+        Value::Array(array) => {
+            if let Some(i) = array.iter().position(|v| contains(v.span(), offset)) {
+                path.push(i.to_string());
+                find_in_value(array.get(i).expect("index is in bounds"), offset, path);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Returns the span of the given key path in the manifest contents.
+fn span_of_key(content: &str, key: &str) -> Option<Range<usize>> {
+    let document = ImDocument::parse(content).ok()?;
+    let keys = Key::parse(key).ok()?;
+    let (last, parents) = keys.split_last()?;
+
+    let mut table: &dyn TableLike = document.as_table();
+    let mut parents = parents.iter();
+    while let Some(key) = parents.next() {
+        // Elements of arrays are addressed by their index, like in `bin.0.name`
+        let mut index = || parents.next()?.get().parse::<usize>().ok();
+        table = match table.get(key.get())? {
+            Item::ArrayOfTables(array) => array.get(index()?)?,
+            Item::Value(Value::Array(array)) => array.get(index()?)?.as_inline_table()?,
+            item => item.as_table_like()?,
+        };
+    }
+
+    let (key, item) = table.get_key_value(last.get())?;
+    match item {
+        Item::Table(table) if table.span().is_some() => table.span(),
+        Item::Value(value) => key.span().zip(value.span()).map(|(k, v)| k.start..v.end),
+        _ => key.span(),
+    }
+}
+
+fn contains(span: Option<Range<usize>>, offset: usize) -> bool {
+    span.is_some_and(|span| span.contains(&offset))
+}
@@ -0,0 +1,308 @@
+//! Package ID specifications, as used by `[replace]`, profile overrides and the
+//! `-p` flag of Cargo (see <https://doc.rust-lang.org/cargo/reference/pkgid-spec.html>).
+
+use crate::{Dependency, Error, GitReference, Manifest, MaybeInherited, Package};
+use std::fmt;
+use std::str::FromStr;
+
+/// A specification of one or more packages, e.g. `serde`, `serde@1.0.200` or
+/// `registry+https://github.com/rust-lang/crates.io-index#serde@1.0.200`.
+///
+/// The legacy syntax `serde:1.0.200` is accepted as well.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct PackageIdSpec {
+    pub name: String,
+    /// A full or partial version, e.g. `1.0.200` or `1.0`.
+    pub version: Option<String>,
+    /// The URL of the source of the package, without the kind prefix and query.
+    ///
+    /// Like in Cargo, sparse registry URLs keep their `sparse+` prefix.
+    pub url: Option<String>,
+    /// The kind of the source, if the URL has a prefix like `registry+`.
+    pub kind: Option<SourceKind>,
+}
+
+/// The kind of source in a [PackageIdSpec], e.g. `registry` in `registry+https://…`.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum SourceKind {
+    Registry,
+    SparseRegistry,
+    Path,
+    /// A git repository, with the reference from the query of the URL,
+    /// e.g. `?branch=main`.
+    Git(GitReference),
+}
+
+impl PackageIdSpec {
+    /// Returns whether a package with the given name and version matches this spec.
+    ///
+    /// Like in Cargo, a partial version matches all versions with the same components,
+    /// e.g. `1.0` matches `1.0.200`. Pre-release versions only match a spec with the
+    /// same pre-release, e.g. `1.0.0-beta.1`. Build metadata is only compared if the
+    /// spec has one, e.g. `1.0.0+abc` doesn't match `1.0.0+def`, but `1.0.0` does.
+    /// The URL is not taken into account.
+    pub fn matches(&self, name: &str, version: &str) -> bool {
+        if self.name != name {
+            return false;
+        }
+        let Some(spec_version) = &self.version else {
+            return true;
+        };
+        let (Ok(spec), Ok(version)) = (
+            PartialVersion::parse(spec_version),
+            PartialVersion::parse(version),
+        ) else {
+            return false;
+        };
+        if version.patch.is_none() || (version.pre.is_some() && spec.pre.is_none()) {
+            return false;
+        }
+        spec.major == version.major
+            && spec.minor.is_none_or(|minor| Some(minor) == version.minor)
+            && spec.patch.is_none_or(|patch| Some(patch) == version.patch)
+            && spec.pre.is_none_or(|pre| Some(pre) == version.pre)
+            && spec.build.is_none_or(|build| Some(build) == version.build)
+    }
+
+    /// Returns whether the given package matches this spec, see [PackageIdSpec::matches()].
+    ///
+    /// A package with an inherited version only matches specs without a version,
+    /// use [Package::inherit_from()] to resolve it first.
+    pub fn matches_package<Metadata>(&self, package: &Package<Metadata>) -> bool {
+        match package.version() {
+            MaybeInherited::Local(version) => self.matches(&package.name, version),
+            MaybeInherited::Inherited { .. } => self.version.is_none() && self.name == package.name,
+        }
+    }
+}
+
+impl FromStr for PackageIdSpec {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Error> {
+        let invalid = |reason: &str| Error::InvalidPackageSpec {
+            spec: s.to_string(),
+            reason: reason.to_string(),
+        };
+
+        if !s.contains("://") {
+            let (name, version) = parse_name_and_version(s).map_err(invalid)?;
+            return Ok(Self {
+                name: name.to_string(),
+                version: version.map(str::to_string),
+                url: None,
+                kind: None,
+            });
+        }
+
+        let (url, fragment) = match s.split_once('#') {
+            Some((url, fragment)) => (url, Some(fragment)),
+            None => (s, None),
+        };
+        let (url, query) = match url.split_once('?') {
+            Some((url, query)) => (url, Some(query)),
+            None => (url, None),
+        };
+        let (scheme, _) = url.split_once("://").unwrap_or_default();
+        let (kind, url) = match scheme.split_once('+') {
+            Some(("registry", _)) => (Some(SourceKind::Registry), &url["registry+".len()..]),
+            Some(("sparse", _)) => (Some(SourceKind::SparseRegistry), url),
+            Some(("path", _)) => (Some(SourceKind::Path), &url["path+".len()..]),
+            Some(("git", _)) => {
+                let reference = parse_git_query(query).map_err(invalid)?;
+                (Some(SourceKind::Git(reference)), &url["git+".len()..])
+            }
+            Some((kind, _)) => {
+                return Err(invalid(&format!("unsupported source kind `{kind}`")));
+            }
+            None => (None, url),
+        };
+        if query.is_some() && !matches!(kind, Some(SourceKind::Git(_))) {
+            return Err(invalid("only git sources may have a query"));
+        }
+
+        let inferred = inferred_name(url);
+        let (name, version) = match fragment {
+            Some(fragment) if fragment.starts_with(|c: char| c.is_ascii_digit()) => {
+                PartialVersion::parse(fragment).map_err(invalid)?;
+                (inferred, Some(fragment))
+            }
+            Some(fragment) => {
+                let (name, version) = parse_name_and_version(fragment).map_err(invalid)?;
+                (Some(name), version)
+            }
+            None => (inferred, None),
+        };
+        let Some(name) = name.filter(|name| !name.is_empty()) else {
+            return Err(invalid("the package name can't be inferred from the URL"));
+        };
+        validate_name(name).map_err(invalid)?;
+
+        Ok(Self {
+            name: name.to_string(),
+            version: version.map(str::to_string),
+            url: Some(url.to_string()),
+            kind,
+        })
+    }
+}
+
+/// Parses `name`, `name@version` or the legacy `name:version`.
+fn parse_name_and_version(s: &str) -> Result<(&str, Option<&str>), &'static str> {
+    let (name, version) = match s.split_once('@').or_else(|| s.split_once(':')) {
+        Some((name, version)) => (name, Some(version)),
+        None => (s, None),
+    };
+    validate_name(name)?;
+    if let Some(version) = version {
+        PartialVersion::parse(version)?;
+    }
+    Ok((name, version))
+}
+
+/// A version that may omit the minor and patch components, like `1.0`.
+struct PartialVersion<'a> {
+    major: u64,
+    minor: Option<u64>,
+    patch: Option<u64>,
+    pre: Option<&'a str>,
+    build: Option<&'a str>,
+}
+
+impl<'a> PartialVersion<'a> {
+    fn parse(version: &'a str) -> Result<Self, &'static str> {
+        if version.is_empty() {
+            return Err("the version is empty");
+        }
+        let (version, build) = match version.split_once('+') {
+            Some((version, build)) => (version, Some(build)),
+            None => (version, None),
+        };
+        let (numbers, pre) = match version.split_once('-') {
+            Some((numbers, pre)) => (numbers, Some(pre)),
+            None => (version, None),
+        };
+
+        let numbers = numbers
+            .split('.')
+            .map(|number| {
+                if !number.bytes().all(|b| b.is_ascii_digit()) {
+                    return Err("the version must look like `1`, `1.0` or `1.0.0`");
+                }
+                number.parse::<u64>().map_err(|_| "invalid version number")
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let (major, minor, patch) = match numbers[..] {
+            [major] => (major, None, None),
+            [major, minor] => (major, Some(minor), None),
+            [major, minor, patch] => (major, Some(minor), Some(patch)),
+            _ => return Err("the version must look like `1`, `1.0` or `1.0.0`"),
+        };
+        if pre.is_some() && patch.is_none() {
+            return Err("a pre-release version must be a full version, like `1.0.0-beta`");
+        }
+        if pre.is_some_and(str::is_empty) {
+            return Err("the pre-release is empty");
+        }
+        if build.is_some() && patch.is_none() {
+            return Err("build metadata requires a full version, like `1.0.0+abc`");
+        }
+        if build.is_some_and(str::is_empty) {
+            return Err("the build metadata is empty");
+        }
+
+        Ok(Self {
+            major,
+            minor,
+            patch,
+            pre,
+            build,
+        })
+    }
+}
+
+/// Returns the last segment of the path of the URL, without a `.git` suffix.
+fn inferred_name(url: &str) -> Option<&str> {
+    let (_, path) = url.split_once("://")?;
+    let (_, segment) = path.trim_end_matches('/').rsplit_once('/')?;
+    Some(segment.strip_suffix(".git").unwrap_or(segment))
+}
+
+fn validate_name(name: &str) -> Result<(), &'static str> {
+    if name.is_empty() {
+        return Err("the package name is empty");
+    }
+    if !name
+        .chars()
+        .all(|c| c.is_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err("package names may only contain letters, digits, `-` and `_`");
+    }
+    Ok(())
+}
+
+fn parse_git_query(query: Option<&str>) -> Result<GitReference, &'static str> {
+    let Some(query) = query else {
+        return Ok(GitReference::DefaultBranch);
+    };
+    match query.split_once('=') {
+        Some(("branch", branch)) => Ok(GitReference::Branch(branch.to_string())),
+        Some(("tag", tag)) => Ok(GitReference::Tag(tag.to_string())),
+        Some(("rev", rev)) => Ok(GitReference::Rev(rev.to_string())),
+        _ => Err("the query of a git source must be `branch`, `tag` or `rev`"),
+    }
+}
+
+impl fmt::Display for PackageIdSpec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Some(url) = &self.url else {
+            f.write_str(&self.name)?;
+            if let Some(version) = &self.version {
+                write!(f, "@{version}")?;
+            }
+            return Ok(());
+        };
+
+        match &self.kind {
+            Some(SourceKind::Registry) => write!(f, "registry+{url}")?,
+            Some(SourceKind::Path) => write!(f, "path+{url}")?,
+            Some(SourceKind::Git(reference)) => {
+                write!(f, "git+{url}")?;
+                match reference {
+                    GitReference::DefaultBranch => {}
+                    GitReference::Branch(branch) => write!(f, "?branch={branch}")?,
+                    GitReference::Tag(tag) => write!(f, "?tag={tag}")?,
+                    GitReference::Rev(rev) => write!(f, "?rev={rev}")?,
+                }
+            }
+            Some(SourceKind::SparseRegistry) | None => f.write_str(url)?,
+        }
+
+        // Like Cargo, the name is omitted if it can be inferred from the URL
+        if inferred_name(url) == Some(self.name.as_str()) {
+            if let Some(version) = &self.version {
+                write!(f, "#{version}")?;
+            }
+        } else {
+            write!(f, "#{}", self.name)?;
+            if let Some(version) = &self.version {
+                write!(f, "@{version}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<PackageMetadata, WorkspaceMetadata> Manifest<PackageMetadata, WorkspaceMetadata> {
+    /// Returns the entries of the `[replace]` table with their keys parsed as
+    /// [PackageIdSpec]s.
+    ///
+    /// Returns [Error::InvalidPackageSpec] if one of the keys is not a valid spec.
+    pub fn replacements(&self) -> Result<Vec<(PackageIdSpec, &Dependency)>, Error> {
+        self.replace
+            .iter()
+            .flatten()
+            .map(|(spec, dependency)| Ok((spec.parse()?, dependency)))
+            .collect()
+    }
+}
@@ -0,0 +1,168 @@
+//! Application of `[patch]` tables to the dependencies of a package
+//! (see <https://doc.rust-lang.org/cargo/reference/overriding-dependencies.html>).
+
+use crate::source::normalize_git_url;
+use crate::{
+    dependency_table_key, quote_key, Dependency, DependencyKind, DependencySource, Error, Manifest,
+    PatchSet,
+};
+
+/// An entry of a `[patch]` table, see [Manifest::apply_patches()].
+#[derive(Debug, Clone, PartialEq)]
+pub struct PatchEntry<'a> {
+    /// The key of the `[patch]` table, e.g. `crates-io` or a git URL.
+    pub table: &'a str,
+    /// The key of the entry in its table.
+    pub name: &'a str,
+    pub dependency: &'a Dependency,
+    /// The source that the patched dependencies are fetched from instead.
+    pub source: DependencySource,
+}
+
+/// A dependency of a package with the `[patch]` entry that applies to it,
+/// see [Manifest::apply_patches()].
+#[derive(Debug, Clone, PartialEq)]
+pub struct PatchedDependency<'a> {
+    /// The key of the dependency in its table.
+    pub name: &'a str,
+    pub dependency: &'a Dependency,
+    pub kind: DependencyKind,
+    /// The key of the `[target.<platform>]` table that declares the dependency,
+    /// or `None` for the top-level dependency tables.
+    pub target: Option<&'a str>,
+    /// The source that is declared by the dependency itself.
+    pub source: DependencySource,
+    /// The first patch entry that applies to the dependency.
+    pub patch: Option<PatchEntry<'a>>,
+}
+
+impl PatchedDependency<'_> {
+    /// Returns the source that the dependency is fetched from with the patch applied.
+    pub fn effective_source(&self) -> &DependencySource {
+        match &self.patch {
+            Some(patch) => &patch.source,
+            None => &self.source,
+        }
+    }
+}
+
+/// The result of [Manifest::apply_patches()].
+#[derive(Debug, Clone)]
+pub struct PatchReport<'a> {
+    /// All dependencies of the package with a valid source, in the order of their tables.
+    pub dependencies: Vec<PatchedDependency<'a>>,
+    /// The patch entries that do not apply to any of the dependencies.
+    pub unused: Vec<PatchEntry<'a>>,
+    /// The errors of the dependencies and patch entries that are skipped because
+    /// their source is invalid, with the key path of the offending entry.
+    pub errors: Vec<Error>,
+}
+
+impl<PackageMetadata, WorkspaceMetadata> Manifest<PackageMetadata, WorkspaceMetadata> {
+    /// Applies the given `[patch]` tables to all dependencies of this manifest.
+    ///
+    /// Pass the `patch` of this manifest, or of the workspace root manifest for a
+    /// workspace member, since Cargo only reads `[patch]` from the workspace root.
+    ///
+    /// A patch entry applies to a dependency if the key of its table refers to the
+    /// source of the dependency, and both refer to the same package name (taking
+    /// `package` renames into account). Tables are keyed by `crates-io`, the name of
+    /// an alternative registry, or a URL of a git repository or registry index.
+    /// Unlike Cargo, the version of the patch is not checked against the
+    /// requirement of the dependency.
+    ///
+    /// If several patch entries apply to a dependency, e.g. to patch multiple versions
+    /// of a crate, all of them are used, and the first one is reported as its patch.
+    ///
+    /// When checking a whole workspace, a patch entry is unused only if it is unused
+    /// for all members.
+    ///
+    /// Dependencies and patch entries with an invalid source are skipped, and their
+    /// [Error::InvalidDependencySource] is reported in [PatchReport::errors]. This
+    /// includes inherited dependencies, use [Manifest::inherit_dependencies_from()]
+    /// to resolve them first.
+    pub fn apply_patches<'a>(&'a self, patch: &'a PatchSet) -> PatchReport<'a> {
+        let mut errors = Vec::new();
+        let mut entries = Vec::new();
+        for (table, deps) in patch {
+            for (name, dependency) in deps {
+                match dependency.source() {
+                    Ok(source) => entries.push(PatchEntry {
+                        table,
+                        name,
+                        dependency,
+                        source,
+                    }),
+                    Err(err) => {
+                        let key = format!("patch.{}.{}", quote_key(table), quote_key(name));
+                        errors.push(err.at_key(&key));
+                    }
+                }
+            }
+        }
+        let mut used = vec![false; entries.len()];
+
+        let mut dependencies = Vec::new();
+        for (target, kind, deps) in self.dependency_tables() {
+            for (name, dependency) in deps {
+                let source = match dependency.source() {
+                    Ok(source) => source,
+                    Err(err) => {
+                        let table = dependency_table_key(target, kind);
+                        errors.push(err.at_key(&format!("{table}.{}", quote_key(name))));
+                        continue;
+                    }
+                };
+                let package = dependency.package().unwrap_or(name);
+                let mut patch = None;
+                for (index, entry) in entries.iter().enumerate() {
+                    if entry.dependency.package().unwrap_or(entry.name) == package
+                        && table_matches(entry.table, &source)
+                    {
+                        used[index] = true;
+                        patch.get_or_insert_with(|| entry.clone());
+                    }
+                }
+                dependencies.push(PatchedDependency {
+                    name,
+                    dependency,
+                    kind,
+                    target,
+                    source,
+                    patch,
+                });
+            }
+        }
+
+        let unused = entries
+            .into_iter()
+            .zip(used)
+            .filter(|(_, used)| !used)
+            .map(|(entry, _)| entry)
+            .collect();
+        PatchReport {
+            dependencies,
+            unused,
+            errors,
+        }
+    }
+}
+
+/// Checks whether the key of a `[patch]` table refers to the given source.
+fn table_matches(table: &str, source: &DependencySource) -> bool {
+    if !table.contains("://") {
+        return match source {
+            DependencySource::CratesIo => table == "crates-io",
+            DependencySource::Registry(registry) => table == registry,
+            _ => false,
+        };
+    }
+
+    let table = normalize_git_url(table);
+    match source {
+        DependencySource::CratesIo => crate::source::is_crates_io_index(&table),
+        DependencySource::RegistryIndex(index) => normalize_git_url(index) == table,
+        DependencySource::Git { url, .. } => *url == table,
+        _ => false,
+    }
+}
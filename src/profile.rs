@@ -0,0 +1,351 @@
+//! Resolution of profiles with `inherits` and Cargo's built-in defaults
+//! (see <https://doc.rust-lang.org/cargo/reference/profiles.html>).
+
+use crate::{
+    DebugInfo, Error, Lto, OptLevel, PanicStrategy, Profile, ProfilePackageSpec, Profiles,
+    SplitDebuginfo, StripSetting,
+};
+use serde::de::Error as _;
+use serde::{Deserialize, Deserializer};
+use std::collections::BTreeMap;
+
+/// A profile with all settings filled in, see [Profiles::resolve()].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResolvedProfile {
+    /// The name of the profile, e.g. `release`.
+    pub name: String,
+    pub opt_level: OptLevel,
+    pub debug: DebugInfo,
+    /// `None` if not set, since the default depends on the target platform.
+    pub split_debuginfo: Option<SplitDebuginfo>,
+    pub strip: StripSetting,
+    pub debug_assertions: bool,
+    pub overflow_checks: bool,
+    pub lto: Lto,
+    pub panic: PanicStrategy,
+    pub incremental: bool,
+    pub codegen_units: u16,
+    pub rpath: bool,
+}
+
+impl ResolvedProfile {
+    /// The built-in defaults of the `dev` profile.
+    pub fn dev() -> Self {
+        Self {
+            name: "dev".to_string(),
+            opt_level: OptLevel::O0,
+            debug: DebugInfo::Full,
+            split_debuginfo: None,
+            strip: StripSetting::None,
+            debug_assertions: true,
+            overflow_checks: true,
+            lto: Lto::ThinLocal,
+            panic: PanicStrategy::Unwind,
+            incremental: true,
+            codegen_units: 256,
+            rpath: false,
+        }
+    }
+
+    /// The built-in defaults of the `release` profile.
+    pub fn release() -> Self {
+        Self {
+            name: "release".to_string(),
+            opt_level: OptLevel::O3,
+            debug: DebugInfo::None,
+            split_debuginfo: None,
+            strip: StripSetting::None,
+            debug_assertions: false,
+            overflow_checks: false,
+            lto: Lto::ThinLocal,
+            panic: PanicStrategy::Unwind,
+            incremental: false,
+            codegen_units: 16,
+            rpath: false,
+        }
+    }
+
+    /// Overrides all settings that are set in the given profile.
+    fn apply(&mut self, profile: &Profile) {
+        let Profile {
+            opt_level,
+            debug,
+            rpath,
+            inherits: _,
+            lto,
+            debug_assertions,
+            codegen_units,
+            panic,
+            incremental,
+            overflow_checks,
+            strip,
+            package: _,
+            split_debuginfo,
+            build_override: _,
+            extra: _,
+        } = profile;
+
+        set(&mut self.opt_level, opt_level.as_ref());
+        set(&mut self.debug, debug.as_deref());
+        set(&mut self.rpath, rpath.as_ref());
+        set(&mut self.lto, lto.as_deref());
+        set(&mut self.debug_assertions, debug_assertions.as_ref());
+        set(&mut self.codegen_units, codegen_units.as_ref());
+        set(&mut self.panic, panic.as_ref());
+        set(&mut self.incremental, incremental.as_ref());
+        set(&mut self.overflow_checks, overflow_checks.as_ref());
+        set(&mut self.strip, strip.as_ref());
+        if split_debuginfo.is_some() {
+            self.split_debuginfo = *split_debuginfo;
+        }
+    }
+}
+
+fn set<T: Clone>(setting: &mut T, value: Option<&T>) {
+    if let Some(value) = value {
+        *setting = value.clone();
+    }
+}
+
+impl Profile {
+    /// Returns the settings that apply to the package with the given name and version.
+    ///
+    /// Like Cargo, an override for the package name takes precedence over the `"*"`
+    /// override, which takes precedence over the settings of the profile itself.
+    /// An override with a version takes precedence over one without.
+    ///
+    /// Note that `"*"` only applies to dependencies, so pass `is_member = true` for
+    /// packages that are members of the workspace.
+    ///
+    /// The returned profile has no package overrides.
+    pub fn for_package(&self, name: &str, version: &str, is_member: bool) -> Profile {
+        let mut profile = self.clone();
+        let overrides = std::mem::take(&mut profile.package);
+
+        if !is_member {
+            if let Some(all) = overrides.get(&ProfilePackageSpec::All) {
+                profile.merge_settings(all);
+            }
+        }
+
+        let mut matching = overrides
+            .iter()
+            .filter_map(|(spec, profile)| match spec {
+                ProfilePackageSpec::Spec(spec) if spec.matches(name, version) => {
+                    Some((spec, profile))
+                }
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        matching.sort_by_key(|(spec, _)| spec.version.is_some());
+        for (_, package_profile) in matching {
+            profile.merge_settings(package_profile);
+        }
+
+        profile
+    }
+
+    /// Overrides the settings of this profile that are set in `other`, and merges
+    /// its package overrides and `build-override`.
+    ///
+    /// This is used for the profiles of an `inherits` chain, so `inherits` itself
+    /// and unknown keys are not merged.
+    fn merge(&mut self, other: &Profile) {
+        self.merge_settings(other);
+
+        for (spec, profile) in &other.package {
+            self.package
+                .entry(spec.clone())
+                .or_default()
+                .merge_settings(profile);
+        }
+        if let Some(build_override) = &other.build_override {
+            self.build_override
+                .get_or_insert_with(Default::default)
+                .merge_settings(build_override);
+        }
+    }
+
+    /// Overrides the codegen settings of this profile that are set in `other`.
+    fn merge_settings(&mut self, other: &Profile) {
+        let Profile {
+            opt_level,
+            debug,
+            rpath,
+            inherits: _,
+            lto,
+            debug_assertions,
+            codegen_units,
+            panic,
+            incremental,
+            overflow_checks,
+            strip,
+            package: _,
+            split_debuginfo,
+            build_override: _,
+            extra: _,
+        } = other;
+
+        merge(&mut self.opt_level, opt_level);
+        merge(&mut self.debug, debug);
+        merge(&mut self.rpath, rpath);
+        merge(&mut self.lto, lto);
+        merge(&mut self.debug_assertions, debug_assertions);
+        merge(&mut self.codegen_units, codegen_units);
+        merge(&mut self.panic, panic);
+        merge(&mut self.incremental, incremental);
+        merge(&mut self.overflow_checks, overflow_checks);
+        merge(&mut self.strip, strip);
+        merge(&mut self.split_debuginfo, split_debuginfo);
+    }
+
+    /// Like Cargo, rejects the settings that can't be overridden for individual
+    /// packages or build scripts, where `which` is `package` or `build-override`.
+    fn validate_override(&self, which: &str) -> Result<(), Error> {
+        let invalid = [
+            ("inherits", self.inherits.is_some()),
+            ("package", !self.package.is_empty()),
+            ("build-override", self.build_override.is_some()),
+            ("panic", self.panic.is_some()),
+            ("lto", self.lto.is_some()),
+            ("rpath", self.rpath.is_some()),
+        ];
+        match invalid.into_iter().find(|(_, is_set)| *is_set) {
+            Some((key, _)) => Err(Error::InvalidProfileOverride {
+                key: key.to_string(),
+                which: which.to_string(),
+            }),
+            None => Ok(()),
+        }
+    }
+}
+
+pub(crate) fn deserialize_package_overrides<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<BTreeMap<ProfilePackageSpec, Profile>, D::Error> {
+    let overrides = BTreeMap::<ProfilePackageSpec, Profile>::deserialize(deserializer)?;
+    for profile in overrides.values() {
+        profile
+            .validate_override("package")
+            .map_err(D::Error::custom)?;
+    }
+    Ok(overrides)
+}
+
+pub(crate) fn deserialize_build_override<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<Box<Profile>>, D::Error> {
+    let profile = Box::<Profile>::deserialize(deserializer)?;
+    profile
+        .validate_override("build-override")
+        .map_err(D::Error::custom)?;
+    Ok(Some(profile))
+}
+
+fn merge<T: Clone>(setting: &mut Option<T>, value: &Option<T>) {
+    if value.is_some() {
+        setting.clone_from(value);
+    }
+}
+
+impl Profiles {
+    /// Returns the profile with the given name, if it is defined in the manifest.
+    pub fn get(&self, name: &str) -> Option<&Profile> {
+        match name {
+            "release" => self.release.as_ref(),
+            "dev" => self.dev.as_ref(),
+            "test" => self.test.as_ref(),
+            "bench" => self.bench.as_ref(),
+            "doc" => self.doc.as_ref(),
+            _ => self.custom.get(name),
+        }
+    }
+
+    /// Returns the effective settings of the profile with the given name.
+    ///
+    /// This follows the `inherits` chain of custom profiles down to one of the
+    /// built-in profiles, and layers the settings on top of Cargo's defaults for
+    /// `dev` or `release`. Like Cargo, `test` and `doc` inherit from `dev`, and
+    /// `bench` inherits from `release`. Unless `strip` is set explicitly, it
+    /// defaults to `"debuginfo"` for profiles without debuginfo.
+    ///
+    /// Package overrides and `build-override` are not applied, see
+    /// [Profiles::resolve_for_package()].
+    ///
+    /// Returns [Error::UnknownProfile] if a custom profile is not defined,
+    /// [Error::MissingProfileInherits] if it has no `inherits` key, and
+    /// [Error::ProfileInheritanceCycle] if the `inherits` chain is a loop.
+    pub fn resolve(&self, name: &str) -> Result<ResolvedProfile, Error> {
+        let (defaults, profile) = self.merged(name)?;
+        Ok(finish(defaults, &profile, name))
+    }
+
+    /// Like [Profiles::resolve()], but also applies the package overrides of the
+    /// profile for the given package, see [Profile::for_package()].
+    pub fn resolve_for_package(
+        &self,
+        name: &str,
+        package_name: &str,
+        package_version: &str,
+        is_member: bool,
+    ) -> Result<ResolvedProfile, Error> {
+        let (defaults, profile) = self.merged(name)?;
+        let profile = profile.for_package(package_name, package_version, is_member);
+        Ok(finish(defaults, &profile, name))
+    }
+
+    /// Returns the defaults of the built-in profile at the root of the `inherits`
+    /// chain, and the merged settings of all profiles in the chain.
+    fn merged(&self, name: &str) -> Result<(ResolvedProfile, Profile), Error> {
+        let mut chain: Vec<&str> = Vec::new();
+        let mut current = name;
+        let defaults = loop {
+            if chain.contains(&current) {
+                let mut chain: Vec<String> = chain.iter().map(|name| name.to_string()).collect();
+                chain.push(current.to_string());
+                return Err(Error::ProfileInheritanceCycle { chain });
+            }
+            chain.push(current);
+
+            current = match current {
+                "dev" => break ResolvedProfile::dev(),
+                "release" => break ResolvedProfile::release(),
+                "test" | "doc" => "dev",
+                "bench" => "release",
+                custom => {
+                    let profile = self
+                        .custom
+                        .get(custom)
+                        .ok_or_else(|| Error::UnknownProfile {
+                            name: custom.to_string(),
+                        })?;
+                    profile
+                        .inherits
+                        .as_deref()
+                        .ok_or_else(|| Error::MissingProfileInherits {
+                            name: custom.to_string(),
+                        })?
+                }
+            };
+        };
+
+        let mut merged = Profile::default();
+        for name in chain.iter().rev() {
+            if let Some(profile) = self.get(name) {
+                merged.merge(profile);
+            }
+        }
+        Ok((defaults, merged))
+    }
+}
+
+fn finish(mut resolved: ResolvedProfile, profile: &Profile, name: &str) -> ResolvedProfile {
+    resolved.apply(profile);
+
+    // Cargo strips the debuginfo of the standard library if the profile has none
+    if profile.strip.is_none() && resolved.debug == DebugInfo::None {
+        resolved.strip = StripSetting::Debuginfo;
+    }
+    resolved.name = name.to_string();
+    resolved
+}
@@ -0,0 +1,179 @@
+//! The source that a dependency is fetched from
+//! (see <https://doc.rust-lang.org/cargo/reference/specifying-dependencies.html>).
+
+use crate::{Dependency, DependencyDetail, Error};
+use std::fmt;
+
+/// The index URLs that refer to crates.io, normalized with [normalize_git_url()].
+const CRATES_IO_INDEX: &[&str] = &[
+    "https://github.com/rust-lang/crates.io-index",
+    "sparse+https://index.crates.io",
+];
+
+/// Where a dependency is fetched from, see [Dependency::source()].
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum DependencySource {
+    /// The default registry, also used for `registry = "crates-io"`.
+    CratesIo,
+    /// An alternative registry, configured by name, e.g. `registry = "my-registry"`.
+    Registry(String),
+    /// An alternative registry, given by the URL of its index.
+    RegistryIndex(String),
+    /// A git repository.
+    ///
+    /// The URL is normalized, so that different spellings of the same repository
+    /// compare equal, e.g. `https://github.com/a/b` and `https://github.com/a/b.git/`.
+    Git {
+        url: String,
+        reference: GitReference,
+    },
+    /// A local path, relative to the manifest or to the path base of the
+    /// dependency, see [crate::DependencyDetail::base].
+    ///
+    /// Cargo uses the path when building locally, even if the dependency also
+    /// specifies a registry to use when it is published.
+    Path(String),
+}
+
+/// The commit of a git dependency that is checked out.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub enum GitReference {
+    /// The `HEAD` of the repository.
+    #[default]
+    DefaultBranch,
+    Branch(String),
+    Tag(String),
+    Rev(String),
+}
+
+impl fmt::Display for GitReference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::DefaultBranch => f.write_str("HEAD"),
+            Self::Branch(branch) => write!(f, "branch={branch}"),
+            Self::Tag(tag) => write!(f, "tag={tag}"),
+            Self::Rev(rev) => write!(f, "rev={rev}"),
+        }
+    }
+}
+
+impl Dependency {
+    /// Returns where this dependency is fetched from.
+    ///
+    /// Returns [Error::InvalidDependencySource] if the source keys contradict each
+    /// other, e.g. `git` and `path`, or `branch` and `rev`, or if the dependency is
+    /// inherited from the workspace. Use [crate::Manifest::inherit_dependencies_from()]
+    /// to resolve inherited dependencies first.
+    pub fn source(&self) -> Result<DependencySource, Error> {
+        match self {
+            Dependency::Simple(_) => Ok(DependencySource::CratesIo),
+            Dependency::Detailed(detail) => detail.source(),
+            Dependency::Inherited(_) => Err(invalid(
+                "the source of an inherited dependency is defined by the workspace",
+            )),
+        }
+    }
+}
+
+impl DependencyDetail {
+    /// Returns where this dependency is fetched from, see [Dependency::source()].
+    pub fn source(&self) -> Result<DependencySource, Error> {
+        let reference = [
+            (
+                "branch",
+                self.branch
+                    .as_ref()
+                    .map(|b| GitReference::Branch(b.clone())),
+            ),
+            (
+                "tag",
+                self.tag.as_ref().map(|t| GitReference::Tag(t.clone())),
+            ),
+            (
+                "rev",
+                self.rev.as_ref().map(|r| GitReference::Rev(r.clone())),
+            ),
+        ]
+        .into_iter()
+        .filter_map(|(key, reference)| Some((key, reference?)))
+        .collect::<Vec<_>>();
+        if let [(first, _), (second, _), ..] = reference[..] {
+            return Err(invalid(&format!(
+                "only one of `branch`, `tag` or `rev` is allowed, found `{first}` and `{second}`"
+            )));
+        }
+        let reference = reference.into_iter().next();
+
+        if self.base.is_some() && self.path.is_none() {
+            return Err(invalid("`base` is only allowed for path dependencies"));
+        }
+
+        if let Some(git) = &self.git {
+            for (key, value) in [
+                ("path", &self.path),
+                ("registry", &self.registry),
+                ("registry-index", &self.registry_index),
+            ] {
+                if value.is_some() {
+                    return Err(invalid(&format!("only one of `git` or `{key}` is allowed")));
+                }
+            }
+            return Ok(DependencySource::Git {
+                url: normalize_git_url(git),
+                reference: reference.map(|(_, r)| r).unwrap_or_default(),
+            });
+        }
+        if let Some((key, _)) = reference {
+            return Err(invalid(&format!(
+                "`{key}` is only allowed for git dependencies"
+            )));
+        }
+        if self.registry.is_some() && self.registry_index.is_some() {
+            return Err(invalid(
+                "only one of `registry` or `registry-index` is allowed",
+            ));
+        }
+
+        if let Some(path) = &self.path {
+            return Ok(DependencySource::Path(path.clone()));
+        }
+        match (&self.registry, &self.registry_index) {
+            (Some(registry), _) if registry == "crates-io" => Ok(DependencySource::CratesIo),
+            (Some(registry), _) => Ok(DependencySource::Registry(registry.clone())),
+            (_, Some(index)) if is_crates_io_index(&normalize_git_url(index)) => {
+                Ok(DependencySource::CratesIo)
+            }
+            (_, Some(index)) => Ok(DependencySource::RegistryIndex(index.clone())),
+            (None, None) => Ok(DependencySource::CratesIo),
+        }
+    }
+}
+
+fn invalid(reason: &str) -> Error {
+    Error::InvalidDependencySource {
+        reason: reason.to_string(),
+    }
+}
+
+/// Returns `true` if the normalized URL is the index of crates.io.
+pub(crate) fn is_crates_io_index(url: &str) -> bool {
+    CRATES_IO_INDEX.contains(&url)
+}
+
+/// Normalizes a git URL like Cargo does to identify a repository: trailing slashes
+/// and a `.git` suffix are removed, and GitHub URLs are compared case-insensitively.
+pub(crate) fn normalize_git_url(url: &str) -> String {
+    let mut url = url.trim_end_matches('/');
+    url = url.strip_suffix(".git").unwrap_or(url);
+    let url = url.trim_end_matches('/');
+
+    let is_github = url
+        .split_once("://")
+        .and_then(|(_, rest)| rest.split(['/', ':']).next())
+        .is_some_and(|host| host.eq_ignore_ascii_case("github.com"));
+    if is_github {
+        url.to_ascii_lowercase()
+    } else {
+        url.to_string()
+    }
+}
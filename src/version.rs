@@ -0,0 +1,56 @@
+//! Typed versions and version requirements, available with the `semver` feature.
+
+use crate::{Dependency, DependencyDetail, Error, MaybeInherited, Package, True};
+use semver::{Version, VersionReq};
+
+impl Dependency {
+    /// Returns the version requirement of this dependency, see [Dependency::req()].
+    ///
+    /// Like Cargo, a requirement without an operator is a caret requirement,
+    /// i.e. `1.2` means `^1.2`.
+    ///
+    /// Returns [Error::InvalidVersionReq] if the requirement can't be parsed, and
+    /// [Error::InheritedVersionReq] for inherited dependencies, which have to be
+    /// resolved with [crate::Manifest::inherit_dependencies_from()] first.
+    pub fn version_req(&self) -> Result<VersionReq, Error> {
+        match self {
+            Dependency::Inherited(_) => Err(Error::InheritedVersionReq),
+            dep => parse_version_req(dep.req()),
+        }
+    }
+}
+
+impl DependencyDetail {
+    /// Returns the version requirement of this dependency, or `None` if it has none.
+    ///
+    /// Returns [Error::InvalidVersionReq] if the requirement can't be parsed.
+    pub fn version_req(&self) -> Result<Option<VersionReq>, Error> {
+        self.version.as_deref().map(parse_version_req).transpose()
+    }
+}
+
+impl<Metadata> Package<Metadata> {
+    /// Returns the effective version of the package, see [Package::version()].
+    ///
+    /// Returns [Error::InvalidVersion] if the version can't be parsed.
+    pub fn semver_version(&self) -> Result<MaybeInherited<Version>, Error> {
+        match self.version() {
+            MaybeInherited::Local(version) => Ok(MaybeInherited::Local(parse_version(version)?)),
+            MaybeInherited::Inherited { .. } => Ok(MaybeInherited::Inherited { workspace: True }),
+        }
+    }
+}
+
+fn parse_version(version: &str) -> Result<Version, Error> {
+    Version::parse(version).map_err(|err| Error::InvalidVersion {
+        version: version.to_string(),
+        reason: err.to_string(),
+    })
+}
+
+fn parse_version_req(req: &str) -> Result<VersionReq, Error> {
+    VersionReq::parse(req).map_err(|err| Error::InvalidVersionReq {
+        req: req.to_string(),
+        reason: err.to_string(),
+    })
+}
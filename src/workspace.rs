@@ -0,0 +1,462 @@
+//! Workspace root discovery and member loading
+//! (see <https://doc.rust-lang.org/cargo/reference/workspaces.html#root-package>).
+
+use crate::{AbstractFilesystem, Error, Manifest, Value, Workspace};
+use serde::Deserialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::io;
+use std::path::{Component, Path, PathBuf};
+use std::str::FromStr;
+
+/// Describes how a manifest relates to its workspace, see [find_workspace_root()].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WorkspaceMembership {
+    /// The manifest has both a `[workspace]` and a `[package]` table,
+    /// so it is the root package of its workspace.
+    Root,
+    /// The manifest has a `[workspace]` table, but no `[package]` table.
+    Virtual,
+    /// The manifest is a member of the workspace defined by the manifest at `root_manifest_path`.
+    Member { root_manifest_path: PathBuf },
+    /// The manifest does not belong to any workspace.
+    Standalone,
+}
+
+/// Finds the workspace that the manifest at `manifest_path` belongs to, the same way Cargo does.
+///
+/// If the manifest does not define a workspace itself, the `package.workspace` key is used
+/// to locate the workspace root. Without it, the parent directories are searched for the
+/// closest manifest with a `[workspace]` table that does not `exclude` this package.
+///
+/// All paths are relative to the root of the given filesystem, which has to support
+/// [AbstractFilesystem::read_to_string()].
+///
+/// Returns [Error::NotAWorkspaceMember] if the package is not included by the `members`
+/// of the workspace root that was found.
+pub fn find_workspace_root<FS: AbstractFilesystem>(
+    fs: &FS,
+    manifest_path: &Path,
+) -> Result<WorkspaceMembership, Error> {
+    let (manifest, content) =
+        read_manifest(fs, manifest_path)?.ok_or_else(|| not_found(manifest_path))?;
+    if manifest.workspace.is_some() {
+        return Ok(match manifest.package {
+            Some(_) => WorkspaceMembership::Root,
+            None => WorkspaceMembership::Virtual,
+        });
+    }
+
+    let member_dir = parent_dir(manifest_path);
+
+    if let Some(workspace_path) = manifest.package.and_then(|p| p.workspace) {
+        let root_dir = normalize_path(&member_dir.join(workspace_path));
+        let root_manifest_path = root_dir.join("Cargo.toml");
+        // Point at `package.workspace` if it doesn't lead to a workspace root
+        let in_member = |err: Error| {
+            err.at_key_in("package.workspace", &content)
+                .in_file(manifest_path, None)
+        };
+        let Some((root_manifest, root_content)) = read_manifest(fs, &root_manifest_path)? else {
+            return Err(in_member(not_found(&root_manifest_path)));
+        };
+        let Some(workspace) = &root_manifest.workspace else {
+            return Err(in_member(Error::NotAWorkspaceRoot {
+                path: root_manifest_path,
+            }));
+        };
+
+        return check_membership(&root_manifest, workspace, &root_dir, &member_dir)
+            .map_err(|err| err.in_file(&root_manifest_path, Some(&root_content)))
+            .map(|()| WorkspaceMembership::Member { root_manifest_path });
+    }
+
+    for root_dir in member_dir.ancestors().skip(1) {
+        let root_manifest_path = root_dir.join("Cargo.toml");
+        let Some((root_manifest, root_content)) = read_manifest(fs, &root_manifest_path)? else {
+            continue;
+        };
+        let Some(workspace) = &root_manifest.workspace else {
+            continue;
+        };
+        if is_excluded(workspace, root_dir, &member_dir) {
+            continue;
+        }
+
+        return check_membership(&root_manifest, workspace, root_dir, &member_dir)
+            .map_err(|err| err.in_file(&root_manifest_path, Some(&root_content)))
+            .map(|()| WorkspaceMembership::Member { root_manifest_path });
+    }
+
+    Ok(WorkspaceMembership::Standalone)
+}
+
+fn check_membership(
+    root_manifest: &Manifest,
+    workspace: &Workspace,
+    root_dir: &Path,
+    member_dir: &Path,
+) -> Result<(), Error> {
+    if is_member(root_manifest, workspace, root_dir, member_dir)? {
+        return Ok(());
+    }
+
+    Err(Error::NotAWorkspaceMember {
+        member: member_dir.join("Cargo.toml"),
+        root: root_dir.join("Cargo.toml"),
+    }
+    .at_key("workspace.members"))
+}
+
+/// Loads all members of a workspace through an [AbstractFilesystem].
+///
+/// All paths are relative to the root of the given filesystem, which has to support
+/// [AbstractFilesystem::read_to_string()].
+pub struct WorkspaceLoader<'a, FS> {
+    fs: &'a FS,
+    root_dir: PathBuf,
+}
+
+/// The result of [WorkspaceLoader::load()].
+#[derive(Debug, Clone, PartialEq)]
+pub struct LoadedWorkspace<Metadata = Value> {
+    /// The manifest of the workspace root.
+    pub root: Manifest<Metadata>,
+    /// The manifests of all workspace members, keyed by their directory.
+    ///
+    /// This includes the root package, unless the root manifest is a virtual manifest.
+    pub members: BTreeMap<PathBuf, Manifest<Metadata>>,
+    /// The directories of the members that are selected when no package is specified.
+    pub default_members: Vec<PathBuf>,
+}
+
+impl<'a, FS: AbstractFilesystem> WorkspaceLoader<'a, FS> {
+    /// Creates a loader for the workspace whose root manifest is located in `root_dir`.
+    pub fn new(fs: &'a FS, root_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            fs,
+            root_dir: normalize_path(&root_dir.into()),
+        }
+    }
+
+    /// Loads the workspace root manifest and the manifests of all workspace members.
+    ///
+    /// The member manifests are completed with
+    /// [Manifest::complete_from_abstract_filesystem()]. Returns
+    /// [Error::MissingMemberManifest] for the first member directory that does not
+    /// contain a `Cargo.toml` file.
+    pub fn load<Metadata: for<'de> Deserialize<'de>>(
+        &self,
+    ) -> Result<LoadedWorkspace<Metadata>, Error> {
+        let root_manifest_path = self.root_dir.join("Cargo.toml");
+        let root_content = self
+            .fs
+            .read_to_string(&root_manifest_path)
+            .map_err(|err| Error::from(err).in_file(&root_manifest_path, None))?;
+        let in_root = |err: Error| err.in_file(&root_manifest_path, Some(&root_content));
+        let root = Manifest::<Metadata>::from_slice_with_metadata(root_content.as_bytes())
+            .map_err(in_root)?;
+        let Some(workspace) = &root.workspace else {
+            return Err(in_root(Error::NotAWorkspaceRoot {
+                path: root_manifest_path.clone(),
+            }));
+        };
+
+        let mut member_paths = self.expand_members(workspace).map_err(in_root)?;
+        member_paths.extend(
+            path_dependencies(&root)
+                .map(|path| normalize_path(&self.root_dir.join(path)))
+                .filter(|path| is_within(path, &self.root_dir))
+                .filter(|path| !is_excluded(workspace, &self.root_dir, path)),
+        );
+        if root.package.is_some() {
+            member_paths.insert(self.root_dir.clone());
+        }
+
+        let default_members = match &workspace.default_members {
+            Some(default_members) => {
+                let mut paths = BTreeSet::new();
+                for default_member in default_members {
+                    let expanded = self
+                        .expand(default_member)
+                        .map_err(|err| in_root(err.at_key("workspace.default-members")))?;
+                    paths.extend(expanded);
+                }
+                if let Some(path) = paths.iter().find(|path| !member_paths.contains(*path)) {
+                    let err = Error::NotAWorkspaceMember {
+                        member: path.join("Cargo.toml"),
+                        root: root_manifest_path.clone(),
+                    };
+                    return Err(in_root(err.at_key("workspace.default-members")));
+                }
+                paths.into_iter().collect()
+            }
+            None if root.package.is_some() => vec![self.root_dir.clone()],
+            None => member_paths.iter().cloned().collect(),
+        };
+
+        let mut members = BTreeMap::new();
+        for path in member_paths {
+            let manifest = if path == self.root_dir {
+                let mut manifest =
+                    Manifest::from_slice_with_metadata(root_content.as_bytes()).map_err(in_root)?;
+                manifest
+                    .complete_from_abstract_filesystem(&SubFilesystem::new(self.fs, &path))
+                    .map_err(in_root)?;
+                manifest
+            } else {
+                self.load_member(&path).map_err(|err| match err {
+                    // The member manifest doesn't exist, so point at the workspace root
+                    Error::MissingMemberManifest { .. } => in_root(err),
+                    err => err,
+                })?
+            };
+            members.insert(path, manifest);
+        }
+
+        Ok(LoadedWorkspace {
+            root,
+            members,
+            default_members,
+        })
+    }
+
+    /// Returns the directories of all workspace members that are matched
+    /// by the `members` patterns and not excluded by `exclude`.
+    ///
+    /// Glob matches that are not directories are ignored. Like in Cargo,
+    /// a `**` component matches any number of nested directories, including none.
+    pub fn expand_members<Metadata>(
+        &self,
+        workspace: &Workspace<Metadata>,
+    ) -> Result<BTreeSet<PathBuf>, Error> {
+        let mut paths = BTreeSet::new();
+        for member in &workspace.members {
+            paths.extend(
+                self.expand(member)
+                    .map_err(|err| err.at_key("workspace.members"))?,
+            );
+        }
+        paths.retain(|path| !is_excluded(workspace, &self.root_dir, path));
+        Ok(paths)
+    }
+
+    fn expand(&self, pattern: &str) -> Result<Vec<PathBuf>, Error> {
+        let mut paths = vec![PathBuf::new()];
+        let mut globbed = false;
+        for component in normalize_path(&self.root_dir.join(pattern)).components() {
+            let component = component.as_os_str().to_string_lossy();
+            if !component.contains(['*', '?', '[']) {
+                paths.iter_mut().for_each(|path| path.push(&*component));
+                // Below a glob, only existing directories are matches
+                if globbed {
+                    paths.retain(|path| self.fs.file_names_in(path).is_ok());
+                }
+                continue;
+            }
+            globbed = true;
+            if component == "**" {
+                let mut matches = Vec::new();
+                for path in paths {
+                    self.push_directories(path, &mut matches);
+                }
+                paths = matches;
+                continue;
+            }
+
+            let component_pattern =
+                glob::Pattern::new(&component).map_err(|err| Error::InvalidGlob {
+                    pattern: pattern.to_string(),
+                    reason: err.msg.to_string(),
+                })?;
+
+            let mut matches = Vec::new();
+            for path in paths {
+                let Ok(file_names) = self.fs.file_names_in(&path) else {
+                    continue;
+                };
+                for file_name in file_names {
+                    let candidate = path.join(&*file_name);
+                    if component_pattern.matches_with(&file_name, MATCH_OPTIONS)
+                        && self.fs.file_names_in(&candidate).is_ok()
+                    {
+                        matches.push(candidate);
+                    }
+                }
+            }
+            paths = matches;
+        }
+        Ok(paths)
+    }
+
+    /// Pushes `path` and all directories below it.
+    fn push_directories(&self, path: PathBuf, out: &mut Vec<PathBuf>) {
+        let Ok(file_names) = self.fs.file_names_in(&path) else {
+            return;
+        };
+        for file_name in file_names {
+            self.push_directories(path.join(&*file_name), out);
+        }
+        out.push(path);
+    }
+
+    fn load_member<Metadata: for<'de> Deserialize<'de>>(
+        &self,
+        path: &Path,
+    ) -> Result<Manifest<Metadata>, Error> {
+        let manifest_path = path.join("Cargo.toml");
+        let content = match self.fs.read_to_string(&manifest_path) {
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {
+                return Err(Error::MissingMemberManifest {
+                    path: path.to_path_buf(),
+                })
+            }
+            result => result.map_err(|err| Error::from(err).in_file(&manifest_path, None))?,
+        };
+
+        let mut manifest = Manifest::from_slice_with_metadata(content.as_bytes())
+            .map_err(|err| err.in_file(&manifest_path, Some(&content)))?;
+        manifest
+            .complete_from_abstract_filesystem(&SubFilesystem::new(self.fs, path))
+            .map_err(|err| err.in_file(&manifest_path, Some(&content)))?;
+        Ok(manifest)
+    }
+}
+
+/// An [AbstractFilesystem] that resolves all paths relative to a subdirectory
+/// of another [AbstractFilesystem].
+struct SubFilesystem<'a, FS> {
+    fs: &'a FS,
+    path: &'a Path,
+}
+
+impl<'a, FS> SubFilesystem<'a, FS> {
+    fn new(fs: &'a FS, path: &'a Path) -> Self {
+        Self { fs, path }
+    }
+}
+
+impl<FS: AbstractFilesystem> AbstractFilesystem for SubFilesystem<'_, FS> {
+    fn file_names_in<T: AsRef<Path>>(&self, rel_path: T) -> io::Result<BTreeSet<Box<str>>> {
+        self.fs.file_names_in(self.path.join(rel_path))
+    }
+
+    fn read_to_string<T: AsRef<Path>>(&self, rel_path: T) -> io::Result<String> {
+        self.fs.read_to_string(self.path.join(rel_path))
+    }
+}
+
+/// A package is a member of a workspace if it matches one of the `members` patterns,
+/// or if it is a path dependency of the root package, unless it is excluded
+/// (see <https://doc.rust-lang.org/cargo/reference/workspaces.html#the-members-and-exclude-fields>).
+fn is_member(
+    root_manifest: &Manifest,
+    workspace: &Workspace,
+    root_dir: &Path,
+    member_dir: &Path,
+) -> Result<bool, Error> {
+    if is_excluded(workspace, root_dir, member_dir) {
+        return Ok(false);
+    }
+
+    for member in &workspace.members {
+        let pattern =
+            member_pattern(root_dir, member).map_err(|err| err.at_key("workspace.members"))?;
+        if pattern.matches_path_with(member_dir, MATCH_OPTIONS) {
+            return Ok(true);
+        }
+    }
+
+    let is_path_dependency = path_dependencies(root_manifest)
+        .any(|path| normalize_path(&root_dir.join(path)) == member_dir);
+    Ok(is_path_dependency)
+}
+
+/// Like Cargo, `exclude` takes precedence over glob patterns in `members`,
+/// but not over members that are listed explicitly.
+fn is_excluded<Metadata>(
+    workspace: &Workspace<Metadata>,
+    root_dir: &Path,
+    member_dir: &Path,
+) -> bool {
+    let starts_with = |path: &String| member_dir.starts_with(normalize_path(&root_dir.join(path)));
+    let excluded = workspace.exclude.iter().flatten().any(starts_with);
+    let explicit_member = workspace.members.iter().any(starts_with);
+    excluded && !explicit_member
+}
+
+fn is_within(path: &Path, dir: &Path) -> bool {
+    path.strip_prefix(dir)
+        .is_ok_and(|rest| !rest.components().any(|c| c == Component::ParentDir))
+}
+
+/// Like in Cargo, `*` and `?` never match a path separator.
+const MATCH_OPTIONS: glob::MatchOptions = glob::MatchOptions {
+    case_sensitive: true,
+    require_literal_separator: true,
+    require_literal_leading_dot: false,
+};
+
+/// Turns an entry of `workspace.members` into a glob pattern that matches
+/// member directories relative to the root of the filesystem.
+fn member_pattern(root_dir: &Path, member: &str) -> Result<glob::Pattern, Error> {
+    let root_dir = root_dir.to_string_lossy();
+    let pattern = normalize_path(&Path::new(&glob::Pattern::escape(&root_dir)).join(member));
+    glob::Pattern::new(&pattern.to_string_lossy()).map_err(|err| Error::InvalidGlob {
+        pattern: member.to_string(),
+        reason: err.msg.to_string(),
+    })
+}
+
+fn path_dependencies<Metadata>(manifest: &Manifest<Metadata>) -> impl Iterator<Item = &str> {
+    manifest
+        .dependency_tables()
+        .into_iter()
+        .flat_map(|(_, _, deps)| deps.values())
+        .filter_map(|dep| dep.detail()?.path.as_deref())
+}
+
+/// Reads and parses the manifest at the given path, returning `None` if it doesn't exist.
+///
+/// The contents are returned as well, to attach the position of later errors.
+fn read_manifest<FS: AbstractFilesystem>(
+    fs: &FS,
+    path: &Path,
+) -> Result<Option<(Manifest, String)>, Error> {
+    match fs.read_to_string(path) {
+        Ok(content) => match Manifest::from_str(&content) {
+            Ok(manifest) => Ok(Some((manifest, content))),
+            Err(err) => Err(err.in_file(path, Some(&content))),
+        },
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(Error::from(err).in_file(path, None)),
+    }
+}
+
+fn not_found(path: &Path) -> Error {
+    let msg = format!("manifest not found: {}", path.display());
+    Error::Io(io::Error::new(io::ErrorKind::NotFound, msg)).in_file(path, None)
+}
+
+fn parent_dir(manifest_path: &Path) -> PathBuf {
+    normalize_path(manifest_path.parent().unwrap_or(Path::new("")))
+}
+
+/// Lexically normalizes a path by removing `.` components and resolving `..`
+/// components where possible, without touching the filesystem.
+pub(crate) fn normalize_path(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => match normalized.components().next_back() {
+                Some(Component::Normal(_)) => {
+                    normalized.pop();
+                }
+                Some(Component::RootDir | Component::Prefix(_)) => {}
+                _ => normalized.push(".."),
+            },
+            component => normalized.push(component),
+        }
+    }
+    normalized
+}
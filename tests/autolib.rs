@@ -1,4 +1,4 @@
-use cargo_manifest::Manifest;
+use cargo_manifest::{Error, Manifest};
 
 mod utils;
 
@@ -141,7 +141,22 @@ fn test_legacy_lib_with_new_edition() {
     "#;
     let tempdir = utils::prepare(manifest, vec!["src/foo.rs"]);
     let error = Manifest::from_path(tempdir.path().join("Cargo.toml")).unwrap_err();
-    insta::assert_snapshot!(error, @"can't find library, rename file to `src/lib.rs` or specify lib.path");
+    insta::assert_snapshot!(error.without_location(), @"can't find library, rename file to `src/lib.rs` or specify lib.path");
+
+    assert!(matches!(
+        error.without_location(),
+        Error::MissingLibraryTarget { expected_paths } if expected_paths == &["src/lib.rs"]
+    ));
+
+    let location = error.location().unwrap();
+    assert_eq!(location.key.as_deref(), Some("lib"));
+    assert_eq!(location.line, Some(7));
+
+    // The 2015 edition also looks for a file named after the library
+    let manifest = manifest.replace("edition = \"2018\"", "");
+    let tempdir = utils::prepare(&manifest, vec!["src/main.rs"]);
+    let error = Manifest::from_path(tempdir.path().join("Cargo.toml")).unwrap_err();
+    insta::assert_snapshot!(error.without_location(), @"can't find library, rename file to one of `src/lib.rs`, `src/foo.rs` or specify lib.path");
 }
 
 #[test]
@@ -0,0 +1,127 @@
+use cargo_manifest::{Cfg, CfgExpr, Error, Manifest, Platform, TargetPlatform};
+use std::str::FromStr;
+
+const RUSTC_CFG: &str = r#"debug_assertions
+panic="unwind"
+target_arch="x86_64"
+target_endian="little"
+target_env="gnu"
+target_family="unix"
+target_feature="sse2"
+target_os="linux"
+target_pointer_width="64"
+target_vendor="unknown"
+unix
+"#;
+
+fn linux() -> TargetPlatform {
+    TargetPlatform::from_rustc_cfg("x86_64-unknown-linux-gnu", RUSTC_CFG).unwrap()
+}
+
+#[test]
+fn parse_platform() {
+    let platform = Platform::from_str(r#"cfg(all(unix, not(target_arch = "wasm32")))"#).unwrap();
+    assert_eq!(
+        platform,
+        Platform::Cfg(CfgExpr::All(vec![
+            CfgExpr::Value(Cfg::Name("unix".into())),
+            CfgExpr::Not(Box::new(CfgExpr::Value(Cfg::KeyPair(
+                "target_arch".into(),
+                "wasm32".into()
+            )))),
+        ]))
+    );
+    assert_eq!(
+        platform.to_string(),
+        r#"cfg(all(unix, not(target_arch = "wasm32")))"#
+    );
+
+    let platform = Platform::from_str("x86_64-pc-windows-gnu").unwrap();
+    assert_eq!(platform, Platform::Name("x86_64-pc-windows-gnu".into()));
+
+    let platform = Platform::from_str("cfg(any(windows, target_os = \"macos\",))").unwrap();
+    assert_eq!(
+        platform.to_string(),
+        r#"cfg(any(windows, target_os = "macos"))"#
+    );
+}
+
+#[test]
+fn invalid_platform() {
+    for platform in [
+        "cfg(all(unix)",
+        "cfg(target_os = )",
+        "cfg(not(unix, windows))",
+        "cfg(unix windows)",
+        "cfg(target_os = \"linux)",
+        "cfg()",
+        "foo bar",
+    ] {
+        let err = Platform::from_str(platform).unwrap_err();
+        assert!(
+            matches!(err, Error::InvalidCfgExpr { ref expr, .. } if expr == platform),
+            "{platform}: {err}"
+        );
+    }
+
+    let err = Platform::from_str("cfg(all(unix)").unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "invalid platform `cfg(all(unix)`: expected `)`, found the end of the expression"
+    );
+}
+
+#[test]
+fn eval() {
+    let linux = linux();
+    assert!(linux.cfgs.contains(&Cfg::Name("unix".into())));
+    assert!(linux
+        .cfgs
+        .contains(&Cfg::KeyPair("target_os".into(), "linux".into())));
+
+    let matches = |platform: &str| linux.matches(&platform.parse().unwrap());
+    assert!(matches("x86_64-unknown-linux-gnu"));
+    assert!(!matches("x86_64-pc-windows-msvc"));
+    assert!(matches("cfg(unix)"));
+    assert!(!matches("cfg(windows)"));
+    assert!(matches(r#"cfg(all(unix, not(target_arch = "wasm32")))"#));
+    assert!(matches(r#"cfg(any(windows, target_os = "linux"))"#));
+    assert!(matches("cfg(all())"));
+    assert!(!matches("cfg(any())"));
+}
+
+#[test]
+fn active_targets() {
+    let m = Manifest::from_str(
+        r#"
+[package]
+name = "foo"
+
+[target.'cfg(unix)'.dependencies]
+libc = "0.2"
+
+[target.'cfg(windows)'.dependencies]
+winapi = "0.3"
+
+[target.x86_64-unknown-linux-gnu.dev-dependencies]
+procfs = "0.16"
+"#,
+    )
+    .unwrap();
+
+    let active = m
+        .active_targets(&linux())
+        .unwrap()
+        .into_iter()
+        .map(|(key, _)| key)
+        .collect::<Vec<_>>();
+    assert_eq!(active, vec!["cfg(unix)", "x86_64-unknown-linux-gnu"]);
+
+    let m = Manifest::from_str("[package]\nname = \"foo\"\n[target.'cfg(unix'.dependencies]\n")
+        .unwrap();
+    let err = m.active_targets(&linux()).unwrap_err();
+    assert_eq!(
+        err.location().unwrap().key.as_deref(),
+        Some("target.\"cfg(unix\"")
+    );
+}
@@ -0,0 +1,324 @@
+use cargo_manifest::{
+    Cfg, DependencyKind, DependencySource, Error, GitReference, Manifest, TargetPlatform,
+};
+use std::str::FromStr;
+
+const MANIFEST: &str = r#"
+[package]
+name = "foo"
+version = "0.1.0"
+
+[features]
+default = ["tls"]
+tls = ["dep:native-tls", "reqwest?/native-tls"]
+http = ["reqwest/json"]
+all = ["http", "tls", "tracing"]
+
+[dependencies]
+serde = "1.0"
+native-tls = { version = "0.2", optional = true }
+reqwest = { version = "0.12", optional = true }
+tracing = { version = "0.1", optional = true }
+
+[dev-dependencies]
+insta = "1"
+
+[build-dependencies]
+cc = "1"
+
+[target.'cfg(target_os = "linux")'.dependencies]
+openssl = { version = "0.10", optional = true }
+libc = "0.2"
+
+[target.'cfg(windows)'.dependencies]
+winapi = "0.3"
+"#;
+
+fn linux() -> TargetPlatform {
+    TargetPlatform::new(
+        "x86_64-unknown-linux-gnu",
+        [
+            Cfg::Name("unix".into()),
+            Cfg::KeyPair("target_os".into(), "linux".into()),
+        ],
+    )
+}
+
+fn active(features: &[&str], kinds: &[DependencyKind]) -> Vec<(String, DependencyKind)> {
+    let m = Manifest::from_str(MANIFEST).unwrap();
+    m.active_dependencies(&linux(), features, kinds)
+        .unwrap()
+        .into_iter()
+        .map(|dep| (dep.name.to_string(), dep.kind))
+        .collect()
+}
+
+fn names(features: &[&str]) -> Vec<String> {
+    active(features, &[DependencyKind::Normal])
+        .into_iter()
+        .map(|(name, _)| name)
+        .collect()
+}
+
+#[test]
+fn kinds_and_targets() {
+    use DependencyKind::*;
+
+    assert_eq!(
+        active(&[], &[Normal, Development, Build]),
+        vec![
+            ("serde".to_string(), Normal),
+            ("insta".to_string(), Development),
+            ("cc".to_string(), Build),
+            ("libc".to_string(), Normal),
+        ]
+    );
+
+    let m = Manifest::from_str(MANIFEST).unwrap();
+    let deps = m.active_dependencies(&linux(), &[], &[Normal]).unwrap();
+    assert_eq!(deps[0].target, None);
+    assert_eq!(deps[1].target, Some(r#"cfg(target_os = "linux")"#));
+}
+
+#[test]
+fn optional_dependencies() {
+    assert_eq!(names(&["default"]), vec!["native-tls", "serde", "libc"]);
+    assert_eq!(names(&["http"]), vec!["reqwest", "serde", "libc"]);
+    assert_eq!(
+        names(&["all"]),
+        vec!["native-tls", "reqwest", "serde", "tracing", "libc"]
+    );
+    // Implicit feature of an optional dependency
+    assert_eq!(names(&["openssl"]), vec!["serde", "libc", "openssl"]);
+    // No implicit feature if the dependency is used with `dep:`
+    let m = Manifest::from_str(MANIFEST).unwrap();
+    let err = m
+        .active_dependencies(&linux(), &["native-tls"], &[DependencyKind::Normal])
+        .unwrap_err();
+    assert!(matches!(err, Error::UnknownFeature { ref feature } if feature == "native-tls"));
+}
+
+#[test]
+fn sources() {
+    let manifest = Manifest::from_str(
+        r#"
+[package]
+name = "foo"
+
+[dependencies]
+simple = "1.0"
+explicit = { version = "1.0", registry = "crates-io" }
+index = { version = "1.0", registry-index = "sparse+https://index.crates.io/" }
+private = { version = "1.0", registry = "my-registry" }
+private-index = { version = "1.0", registry-index = "https://example.com/index" }
+local = { path = "../local", version = "1.0", registry = "my-registry" }
+head = { git = "https://github.com/Foo/Bar.git/" }
+branch = { git = "https://github.com/foo/bar", branch = "main" }
+rev = { git = "ssh://git@example.com/Foo/Bar.git", rev = "abc123" }
+inherited = { workspace = true }
+branch-and-rev = { git = "https://github.com/foo/bar", branch = "main", rev = "abc123" }
+git-and-path = { git = "https://github.com/foo/bar", path = "bar" }
+tag-without-git = { version = "1.0", tag = "v1.0" }
+two-registries = { registry = "my-registry", registry-index = "https://example.com/index" }
+"#,
+    )
+    .unwrap();
+    let deps = manifest.dependencies.as_ref().unwrap();
+    let source = |name: &str| deps[name].source();
+
+    assert_eq!(source("simple").unwrap(), DependencySource::CratesIo);
+    assert_eq!(source("explicit").unwrap(), DependencySource::CratesIo);
+    assert_eq!(source("index").unwrap(), DependencySource::CratesIo);
+    assert!(deps["explicit"].is_crates_io());
+    assert_eq!(
+        source("private").unwrap(),
+        DependencySource::Registry("my-registry".to_string())
+    );
+    assert!(!deps["private"].is_crates_io());
+    assert_eq!(
+        source("private-index").unwrap(),
+        DependencySource::RegistryIndex("https://example.com/index".to_string())
+    );
+    assert_eq!(
+        source("local").unwrap(),
+        DependencySource::Path("../local".to_string())
+    );
+    assert_eq!(
+        source("head").unwrap(),
+        DependencySource::Git {
+            url: "https://github.com/foo/bar".to_string(),
+            reference: GitReference::DefaultBranch,
+        }
+    );
+    assert_eq!(
+        source("branch").unwrap(),
+        DependencySource::Git {
+            url: "https://github.com/foo/bar".to_string(),
+            reference: GitReference::Branch("main".to_string()),
+        }
+    );
+    assert_eq!(
+        source("rev").unwrap(),
+        DependencySource::Git {
+            url: "ssh://git@example.com/Foo/Bar".to_string(),
+            reference: GitReference::Rev("abc123".to_string()),
+        }
+    );
+
+    let errors = [
+        "inherited",
+        "branch-and-rev",
+        "git-and-path",
+        "tag-without-git",
+        "two-registries",
+    ]
+    .map(|name| {
+        let err = source(name).unwrap_err();
+        assert!(matches!(err, Error::InvalidDependencySource { .. }));
+        err.to_string()
+    });
+    assert_eq!(
+        errors,
+        [
+            "invalid dependency source: the source of an inherited dependency is defined by the workspace",
+            "invalid dependency source: only one of `branch`, `tag` or `rev` is allowed, found `branch` and `rev`",
+            "invalid dependency source: only one of `git` or `path` is allowed",
+            "invalid dependency source: `tag` is only allowed for git dependencies",
+            "invalid dependency source: only one of `registry` or `registry-index` is allowed",
+        ]
+    );
+}
+
+#[test]
+fn patches() {
+    let manifest = Manifest::from_str(
+        r#"
+[package]
+name = "foo"
+
+[dependencies]
+serde = "1.0"
+json = { version = "1.0", package = "serde_json" }
+bar = { git = "https://github.com/foo/bar", branch = "main" }
+private = { version = "1.0", registry = "my-registry" }
+
+[target.'cfg(unix)'.dev-dependencies]
+libc = "0.2"
+
+[patch.crates-io]
+serde_json = { path = "../serde_json" }
+libc = { git = "https://github.com/rust-lang/libc.git" }
+rand = { path = "../rand" }
+
+[patch."https://github.com/Foo/Bar.git"]
+bar = { path = "../bar" }
+
+[patch.my-registry]
+private = { path = "../private" }
+other = { path = "../other" }
+"#,
+    )
+    .unwrap();
+
+    let report = manifest.apply_patches(manifest.patch.as_ref().unwrap());
+    assert!(report.errors.is_empty());
+    let patched = report
+        .dependencies
+        .iter()
+        .map(|dep| {
+            let table = dep.patch.as_ref().map(|patch| patch.table);
+            (dep.name, table, dep.effective_source().clone())
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        patched,
+        [
+            (
+                "bar",
+                Some("https://github.com/Foo/Bar.git"),
+                DependencySource::Path("../bar".to_string())
+            ),
+            (
+                "json",
+                Some("crates-io"),
+                DependencySource::Path("../serde_json".to_string())
+            ),
+            (
+                "private",
+                Some("my-registry"),
+                DependencySource::Path("../private".to_string())
+            ),
+            ("serde", None, DependencySource::CratesIo),
+            (
+                "libc",
+                Some("crates-io"),
+                DependencySource::Git {
+                    url: "https://github.com/rust-lang/libc".to_string(),
+                    reference: GitReference::DefaultBranch,
+                }
+            ),
+        ]
+    );
+    assert_eq!(report.dependencies[4].target, Some("cfg(unix)"));
+    assert_eq!(report.dependencies[4].kind, DependencyKind::Development);
+
+    let unused = report
+        .unused
+        .iter()
+        .map(|entry| (entry.table, entry.name))
+        .collect::<Vec<_>>();
+    assert_eq!(unused, [("crates-io", "rand"), ("my-registry", "other")]);
+}
+
+#[test]
+fn patches_with_multiple_matches_and_errors() {
+    let manifest = Manifest::from_str(
+        r#"
+[package]
+name = "foo"
+
+[dependencies]
+serde = "1.0"
+inherited = { workspace = true }
+
+[target.'cfg(unix)'.dependencies]
+libc = { git = "https://github.com/rust-lang/libc", path = "../libc" }
+
+[patch.crates-io]
+serde = { path = "../serde" }
+serde2 = { package = "serde", path = "../serde2" }
+broken = { path = "../broken", git = "https://github.com/foo/broken" }
+
+[patch."https://github.com/rust-lang/crates.io-index"]
+serde = { path = "../serde3" }
+"#,
+    )
+    .unwrap();
+
+    let report = manifest.apply_patches(manifest.patch.as_ref().unwrap());
+    assert_eq!(report.dependencies.len(), 1);
+    let serde = report.dependencies[0].patch.as_ref().unwrap();
+    assert_eq!((serde.table, serde.name), ("crates-io", "serde"));
+    // All patches of `serde` are used, even though only the first one is reported
+    assert!(report.unused.is_empty());
+
+    let errors = report
+        .errors
+        .iter()
+        .map(|err| {
+            assert!(matches!(
+                err.without_location(),
+                Error::InvalidDependencySource { .. }
+            ));
+            err.location().unwrap().key.clone().unwrap()
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        errors,
+        [
+            "patch.crates-io.broken",
+            "dependencies.inherited",
+            "target.\"cfg(unix)\".dependencies.libc",
+        ]
+    );
+}
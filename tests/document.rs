@@ -0,0 +1,159 @@
+use cargo_manifest::{Dependency, DependencyDetail, DependencyKind, Error, ManifestDocument};
+use std::str::FromStr;
+
+const MANIFEST: &str = r#"# The main crate
+[package]
+name = "foo"
+version = "0.1.0"   # bumped by CI
+edition = "2021"
+
+[dependencies]
+# logging
+log = "0.4"
+serde = { version = "1.0", features = ["derive"] }
+
+[dev_dependencies]
+insta = "1"
+"#;
+
+#[test]
+fn untouched_document_round_trips() {
+    let document = ManifestDocument::from_str(MANIFEST).unwrap();
+    assert_eq!(document.to_string(), MANIFEST);
+}
+
+#[test]
+fn set_version() {
+    let mut document = ManifestDocument::from_str(MANIFEST).unwrap();
+    document.set_version("0.2.0").unwrap();
+    assert_eq!(
+        document.to_string(),
+        MANIFEST.replace("\"0.1.0\"", "\"0.2.0\"")
+    );
+    assert_eq!(
+        document
+            .manifest()
+            .unwrap()
+            .package
+            .unwrap()
+            .version()
+            .as_local(),
+        Some("0.2.0")
+    );
+
+    let mut document =
+        ManifestDocument::from_str("[package]\nname = \"foo\"\nversion.workspace = true\n")
+            .unwrap();
+    document.set_version("1.0.0").unwrap();
+    assert_eq!(
+        document.to_string(),
+        "[package]\nname = \"foo\"\nversion = \"1.0.0\"\n"
+    );
+
+    let mut document = ManifestDocument::from_str("[workspace]\n").unwrap();
+    let err = document.set_version("1.0.0").unwrap_err();
+    assert!(matches!(err.without_location(), Error::MissingKey { ref key } if key == "package"));
+    assert_eq!(err.location().unwrap().key.as_deref(), Some("package"));
+}
+
+#[test]
+fn add_and_remove_dependencies() {
+    let mut document = ManifestDocument::from_str(MANIFEST).unwrap();
+    let tokio = Dependency::Detailed(DependencyDetail {
+        version: Some("1".to_string()),
+        features: Some(vec!["full".to_string()]),
+        ..Default::default()
+    });
+    document
+        .add_dependency(DependencyKind::Normal, "tokio", &tokio)
+        .unwrap();
+    document
+        .add_dependency(
+            DependencyKind::Development,
+            "insta",
+            &Dependency::Simple("1.39".to_string()),
+        )
+        .unwrap();
+    document
+        .add_dependency(
+            DependencyKind::Build,
+            "cc",
+            &Dependency::Simple("1".to_string()),
+        )
+        .unwrap();
+    assert!(document.remove_dependency(DependencyKind::Normal, "log"));
+    assert!(!document.remove_dependency(DependencyKind::Build, "log"));
+
+    insta::assert_snapshot!(document.to_string(), @r###"
+    # The main crate
+    [package]
+    name = "foo"
+    version = "0.1.0"   # bumped by CI
+    edition = "2021"
+
+    [dependencies]
+    serde = { version = "1.0", features = ["derive"] }
+    tokio = { version = "1", features = ["full"] }
+
+    [dev_dependencies]
+    insta = "1.39"
+
+    [build-dependencies]
+    cc = "1"
+    "###);
+
+    let manifest = document.manifest().unwrap();
+    assert_eq!(manifest.dependencies.unwrap()["tokio"], tokio);
+}
+
+#[test]
+fn set_dependency_feature() {
+    let mut document = ManifestDocument::from_str(MANIFEST).unwrap();
+    document
+        .set_dependency_feature(DependencyKind::Normal, "log", "std", true)
+        .unwrap();
+    document
+        .set_dependency_feature(DependencyKind::Normal, "serde", "rc", true)
+        .unwrap();
+    document
+        .set_dependency_feature(DependencyKind::Normal, "serde", "derive", false)
+        .unwrap();
+    document
+        .set_dependency_feature(DependencyKind::Development, "insta", "yaml", false)
+        .unwrap();
+
+    insta::assert_snapshot!(document.to_string(), @r###"
+    # The main crate
+    [package]
+    name = "foo"
+    version = "0.1.0"   # bumped by CI
+    edition = "2021"
+
+    [dependencies]
+    # logging
+    log = { version = "0.4", features = ["std"] }
+    serde = { version = "1.0", features = ["rc"] }
+
+    [dev_dependencies]
+    insta = "1"
+    "###);
+
+    let err = document
+        .set_dependency_feature(DependencyKind::Build, "cc", "parallel", true)
+        .unwrap_err();
+    assert!(matches!(
+        err.without_location(),
+        Error::MissingKey { ref key } if key == "build-dependencies.cc"
+    ));
+
+    // Dependencies that aren't tables point at the offending value
+    let mut document =
+        ManifestDocument::from_str("[package]\nname = \"foo\"\n\n[dependencies]\nfoo = 1\n")
+            .unwrap();
+    let err = document
+        .set_dependency_feature(DependencyKind::Normal, "foo", "std", true)
+        .unwrap_err();
+    let location = err.location().unwrap();
+    assert_eq!(location.key.as_deref(), Some("dependencies.foo"));
+    assert_eq!((location.line, location.column), (Some(5), Some(1)));
+}
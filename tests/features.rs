@@ -0,0 +1,449 @@
+use cargo_manifest::{
+    Error, FeatureError, FeatureMatrixMode, FeatureMatrixOptions, FeatureValue, Manifest,
+};
+use std::collections::BTreeSet;
+use std::str::FromStr;
+
+const MANIFEST: &str = r#"
+[package]
+name = "foo"
+version = "0.1.0"
+
+[features]
+default = ["std"]
+std = ["serde?/std", "log/std"]
+tls = ["dep:native-tls"]
+json = ["serde/derive", "dep:serde_json"]
+
+[dependencies]
+serde = { version = "1.0", optional = true }
+serde_json = { version = "1.0", optional = true }
+native-tls = { version = "0.2", optional = true }
+log = "0.4"
+
+[target.'cfg(unix)'.dependencies]
+libc = { version = "0.2", optional = true }
+"#;
+
+fn set(values: &[&str]) -> BTreeSet<String> {
+    values.iter().map(|v| v.to_string()).collect()
+}
+
+#[test]
+fn feature_values() {
+    assert_eq!(
+        FeatureValue::new("std"),
+        FeatureValue::Feature("std".into())
+    );
+    assert_eq!(
+        FeatureValue::new("dep:serde"),
+        FeatureValue::Dep {
+            dep_name: "serde".into()
+        }
+    );
+    assert_eq!(
+        FeatureValue::new("serde?/std"),
+        FeatureValue::DepFeature {
+            dep_name: "serde".into(),
+            dep_feature: "std".into(),
+            weak: true,
+        }
+    );
+    for value in ["std", "dep:serde", "serde/std", "serde?/std"] {
+        assert_eq!(FeatureValue::new(value).to_string(), value);
+    }
+
+    let m = Manifest::from_str(MANIFEST).unwrap();
+    let json = &m.feature_map()["json"];
+    assert_eq!(
+        json[1],
+        FeatureValue::Dep {
+            dep_name: "serde_json".into()
+        }
+    );
+}
+
+#[test]
+fn implicit_features() {
+    let m = Manifest::from_str(MANIFEST).unwrap();
+    let feature_map = m.feature_map();
+    assert_eq!(
+        feature_map.keys().collect::<Vec<_>>(),
+        vec!["default", "json", "libc", "serde", "std", "tls"]
+    );
+    assert_eq!(
+        feature_map["serde"],
+        vec![FeatureValue::Dep {
+            dep_name: "serde".into()
+        }]
+    );
+
+    let m = Manifest::from_str(&MANIFEST.replace(
+        "version = \"0.1.0\"",
+        "version = \"0.1.0\"\nedition = \"2024\"",
+    ))
+    .unwrap();
+    assert_eq!(
+        m.feature_map().keys().collect::<Vec<_>>(),
+        vec!["default", "json", "std", "tls"]
+    );
+}
+
+#[test]
+fn resolve() {
+    let m = Manifest::from_str(MANIFEST).unwrap();
+
+    let resolved = m.resolve_features(&[], true).unwrap();
+    assert_eq!(resolved.features, set(&["default", "std"]));
+    assert!(resolved.dependencies.is_empty());
+    // The weak feature doesn't apply since `serde` is not enabled
+    assert_eq!(
+        resolved.dependency_features.keys().collect::<Vec<_>>(),
+        vec!["log"]
+    );
+
+    let resolved = m.resolve_features(&["json"], true).unwrap();
+    assert_eq!(resolved.features, set(&["default", "json", "serde", "std"]));
+    assert_eq!(resolved.dependencies, set(&["serde", "serde_json"]));
+    assert_eq!(
+        resolved.dependency_features["serde"],
+        set(&["derive", "std"])
+    );
+
+    let resolved = m.resolve_features(&["libc", "tls"], false).unwrap();
+    assert_eq!(resolved.features, set(&["libc", "tls"]));
+    assert_eq!(resolved.dependencies, set(&["libc", "native-tls"]));
+
+    let err = m.resolve_features(&["native-tls"], false).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "the package does not have the feature `native-tls`"
+    );
+}
+
+#[test]
+fn validate() {
+    let m = Manifest::from_str(MANIFEST).unwrap();
+    assert_eq!(m.validate_features(), vec![]);
+
+    let m = Manifest::from_str(
+        r#"
+[package]
+name = "foo"
+version = "0.1.0"
+
+[features]
+"-bad" = []
+a = ["missing", "log", "tls"]
+b = ["dep:log", "dep:nope", "log?/std", "nope/std", "log/std"]
+rand = []
+tls = ["dep:native-tls"]
+
+[dependencies]
+log = "0.4"
+native-tls = { version = "0.2", optional = true }
+rand = { version = "0.8", optional = true }
+
+[build-dependencies]
+cc = { version = "1", optional = true }
+"#,
+    )
+    .unwrap();
+
+    let errors = m
+        .validate_features()
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        errors,
+        vec![
+            "invalid feature name `-bad`: feature names must start with a letter, a digit or `_`",
+            "feature `a` includes `missing` which is neither a dependency nor another feature",
+            "feature `a` includes `log`, but `log` is not an optional dependency",
+            "feature `b` includes `dep:log`, but `log` is not an optional dependency",
+            "feature `b` includes `dep:nope`, but `nope` is not a dependency",
+            "feature `b` includes `log?/std`, but `log` is not an optional dependency",
+            "feature `b` includes `nope/std`, but `nope` is not a dependency",
+            "optional dependency `rand` is not enabled by any feature",
+        ]
+    );
+
+    let m = Manifest::from_str(
+        r#"
+[package]
+name = "foo"
+version = "0.1.0"
+edition = "2024"
+
+[features]
+json = ["serde_json"]
+
+[dependencies]
+serde_json = { version = "1", optional = true }
+"#,
+    )
+    .unwrap();
+    assert_eq!(
+        m.validate_features(),
+        vec![
+            FeatureError::NoImplicitFeature {
+                feature: "json".into(),
+                value: FeatureValue::new("serde_json"),
+                dep_name: "serde_json".into(),
+            },
+            FeatureError::UnusedOptionalDependency {
+                dep_name: "serde_json".into(),
+            },
+        ]
+    );
+
+    // A feature may have the same name as an optional dependency that it enables
+    let m = Manifest::from_str(
+        r#"
+[package]
+name = "foo"
+version = "0.1.0"
+
+[features]
+serde = ["serde/std"]
+
+[dependencies]
+serde = { version = "1", optional = true }
+"#,
+    )
+    .unwrap();
+    assert_eq!(m.validate_features(), vec![]);
+}
+
+const MATRIX_MANIFEST: &str = r#"
+[package]
+name = "foo"
+version = "0.1.0"
+
+[features]
+default = ["std"]
+std = ["alloc"]
+alloc = []
+serde = ["dep:serde"]
+unstable = []
+
+[dependencies]
+serde = { version = "1.0", optional = true }
+log = { version = "0.4", optional = true }
+"#;
+
+#[test]
+fn feature_matrix() {
+    let m = Manifest::from_str(MATRIX_MANIFEST).unwrap();
+
+    let each = m
+        .feature_matrix(&FeatureMatrixOptions {
+            mode: FeatureMatrixMode::EachFeature,
+            exclude: vec!["default".into()],
+            ..Default::default()
+        })
+        .unwrap()
+        .collect::<Vec<_>>();
+    assert_eq!(
+        each,
+        vec![
+            vec![],
+            vec!["alloc"],
+            vec!["serde"],
+            vec!["std"],
+            vec!["unstable"],
+        ]
+    );
+
+    let powerset = m
+        .feature_matrix(&FeatureMatrixOptions {
+            exclude: vec!["default".into(), "unstable".into()],
+            optional_deps: true,
+            ..Default::default()
+        })
+        .unwrap()
+        .collect::<Vec<_>>();
+    // `std` already enables `alloc`, so they are never combined
+    assert_eq!(
+        powerset,
+        vec![
+            vec![],
+            vec!["alloc"],
+            vec!["log"],
+            vec!["serde"],
+            vec!["std"],
+            vec!["alloc", "log"],
+            vec!["alloc", "serde"],
+            vec!["log", "serde"],
+            vec!["log", "std"],
+            vec!["serde", "std"],
+            vec!["alloc", "log", "serde"],
+            vec!["log", "serde", "std"],
+        ]
+    );
+}
+
+#[test]
+fn feature_matrix_depth_and_groups() {
+    let m = Manifest::from_str(MATRIX_MANIFEST).unwrap();
+
+    let matrix = m
+        .feature_matrix(&FeatureMatrixOptions {
+            depth: Some(2),
+            exclude: vec!["default".into(), "alloc".into()],
+            groups: vec![vec!["serde".into(), "unstable".into()]],
+            ..Default::default()
+        })
+        .unwrap()
+        .collect::<Vec<_>>();
+    assert_eq!(
+        matrix,
+        vec![
+            vec![],
+            vec!["serde", "unstable"],
+            vec!["std"],
+            vec!["serde", "std", "unstable"],
+        ]
+    );
+
+    let Err(err) = m.feature_matrix(&FeatureMatrixOptions {
+        exclude: vec!["nope".into()],
+        ..Default::default()
+    }) else {
+        panic!("`nope` is not a feature");
+    };
+    assert!(matches!(err, Error::UnknownFeature { ref feature } if feature == "nope"));
+}
+
+#[test]
+fn feature_matrix_skips_equivalent_combinations() {
+    let m = Manifest::from_str(
+        r#"
+[package]
+name = "foo"
+
+[features]
+default = ["a"]
+a = []
+b = []
+c = []
+"#,
+    )
+    .unwrap();
+
+    let matrix = m
+        .feature_matrix(&FeatureMatrixOptions {
+            depth: Some(2),
+            ..Default::default()
+        })
+        .unwrap()
+        .collect::<Vec<_>>();
+    // `default` only enables `a`, so it is equivalent to `a`
+    assert_eq!(
+        matrix,
+        vec![
+            vec![],
+            vec!["a"],
+            vec!["b"],
+            vec!["c"],
+            vec!["a", "b"],
+            vec!["a", "c"],
+            vec!["b", "c"],
+        ]
+    );
+
+    // The combinations are computed lazily
+    let features = (0..40).map(|i| format!("f{i:02} = []")).collect::<Vec<_>>();
+    let manifest = format!(
+        "[package]\nname = \"foo\"\n[features]\n{}",
+        features.join("\n")
+    );
+    let m = Manifest::from_str(&manifest).unwrap();
+    let mut matrix = m.feature_matrix(&FeatureMatrixOptions::default()).unwrap();
+    assert_eq!(
+        matrix.nth(41),
+        Some(vec!["f00".to_string(), "f01".to_string()])
+    );
+}
+
+#[test]
+fn required_features() {
+    let manifest = format!(
+        r#"{MANIFEST}
+[[bin]]
+name = "cli"
+required-features = ["std", "tls"]
+
+[[example]]
+name = "json"
+required-features = ["serde/derive", "serde_json"]
+
+[[test]]
+name = "unix"
+required-features = ["libc"]
+
+[[bench]]
+name = "broken"
+required-features = ["nope", "dep:serde", "serde?/std", "rand/std"]
+"#
+    );
+    let manifest = Manifest::from_str(&manifest).unwrap();
+
+    let errors = manifest
+        .validate_required_features()
+        .iter()
+        .map(|error| error.to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        errors,
+        [
+            "invalid feature `nope` in required-features of bench `broken`: `nope` is not present in the [features] section",
+            "invalid feature `dep:serde` in required-features of bench `broken`: `dep:` prefixed feature values are not allowed",
+            "invalid feature `serde?/std` in required-features of bench `broken`: optional dependency features with `?` are not allowed",
+            "invalid feature `rand/std` in required-features of bench `broken`: `rand` is not a dependency",
+        ]
+    );
+
+    let status = |features: &[&str]| {
+        let resolved = manifest.resolve_features(features, false).unwrap();
+        manifest
+            .products_for_features(&resolved)
+            .into_iter()
+            .map(|status| {
+                let name = status.product.name.clone().unwrap();
+                (status.kind.table_name(), name, status.missing_features)
+            })
+            .collect::<Vec<_>>()
+    };
+    let built = |features: &[&str]| {
+        let resolved = manifest.resolve_features(features, false).unwrap();
+        manifest
+            .products_for_features(&resolved)
+            .into_iter()
+            .filter(|status| status.is_built())
+            .map(|status| status.product.name.clone().unwrap())
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(
+        status(&["std"]),
+        [
+            ("bin", "cli".to_string(), vec!["tls"]),
+            (
+                "example",
+                "json".to_string(),
+                vec!["serde/derive", "serde_json"]
+            ),
+            ("test", "unix".to_string(), vec!["libc"]),
+            (
+                "bench",
+                "broken".to_string(),
+                vec!["nope", "dep:serde", "serde?/std", "rand/std"]
+            ),
+        ]
+    );
+    assert_eq!(built(&["std", "tls"]), ["cli"]);
+    assert_eq!(built(&["json", "libc"]), ["json", "unix"]);
+    assert!(built(&["std"]).is_empty());
+}
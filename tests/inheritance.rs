@@ -0,0 +1,440 @@
+use cargo_manifest::{
+    Dependency, Edition, Error, LintLevel, Manifest, MaybeInherited, StringOrBool,
+};
+use std::path::Path;
+use std::str::FromStr;
+
+const WORKSPACE_MANIFEST: &str = r#"
+[workspace]
+members = ["bar"]
+
+[workspace.package]
+version = "1.2.3"
+edition = "2021"
+authors = ["Jane Doe <jane@example.com>"]
+license = "MIT OR Apache-2.0"
+rust-version = "1.70"
+include = ["src/**/*.rs"]
+"#;
+
+#[test]
+fn package_fields() {
+    let workspace = Manifest::from_str(WORKSPACE_MANIFEST).unwrap();
+    let mut m = Manifest::from_str(
+        r#"
+[package]
+name = "bar"
+version.workspace = true
+edition.workspace = true
+authors.workspace = true
+license.workspace = true
+rust-version.workspace = true
+include.workspace = true
+description = "local description"
+"#,
+    )
+    .unwrap();
+
+    m.inherit_from(workspace.workspace.as_ref().unwrap(), Path::new(".."))
+        .unwrap();
+
+    let package = m.package.unwrap();
+    assert_eq!(package.version(), MaybeInherited::Local("1.2.3"));
+    assert_eq!(package.edition, Some(MaybeInherited::Local(Edition::E2021)));
+    assert_eq!(
+        package.authors,
+        Some(MaybeInherited::Local(vec![
+            "Jane Doe <jane@example.com>".to_string()
+        ]))
+    );
+    assert_eq!(
+        package.license,
+        Some(MaybeInherited::Local("MIT OR Apache-2.0".to_string()))
+    );
+    assert_eq!(
+        package.rust_version,
+        Some(MaybeInherited::Local("1.70".to_string()))
+    );
+    assert_eq!(
+        package.include,
+        Some(MaybeInherited::Local(vec!["src/**/*.rs".to_string()]))
+    );
+    assert_eq!(
+        package.description,
+        Some(MaybeInherited::Local("local description".to_string()))
+    );
+}
+
+#[test]
+fn missing_package_field() {
+    let workspace = Manifest::from_str(WORKSPACE_MANIFEST).unwrap();
+    let mut m = Manifest::from_str(
+        r#"
+[package]
+name = "bar"
+version.workspace = true
+homepage.workspace = true
+license.workspace = true
+repository.workspace = true
+"#,
+    )
+    .unwrap();
+
+    let err = m
+        .inherit_from(workspace.workspace.as_ref().unwrap(), Path::new(".."))
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        Error::MissingWorkspaceKeys { ref keys } if keys == &["package.homepage", "package.repository"]
+    ));
+    assert_eq!(
+        err.to_string(),
+        "`package.homepage`, `package.repository` are inherited from the workspace, but the workspace does not define them"
+    );
+
+    // The package is left unchanged, even the fields that the workspace defines
+    let package = m.package.unwrap();
+    assert_eq!(package.version, Some(MaybeInherited::inherited()));
+    assert_eq!(package.license, Some(MaybeInherited::inherited()));
+}
+
+#[test]
+fn package_paths() {
+    let workspace = Manifest::from_str(
+        r#"
+[workspace]
+members = ["crates/*"]
+
+[workspace.package]
+readme = "README.md"
+license-file = "LICENSE"
+"#,
+    )
+    .unwrap();
+    let mut m = Manifest::from_str(
+        r#"
+[package]
+name = "bar"
+readme.workspace = true
+license-file.workspace = true
+"#,
+    )
+    .unwrap();
+
+    m.inherit_from(workspace.workspace.as_ref().unwrap(), Path::new("../.."))
+        .unwrap();
+
+    let package = m.package.unwrap();
+    assert_eq!(
+        package.readme,
+        Some(MaybeInherited::Local(StringOrBool::String(
+            "../../README.md".to_string()
+        )))
+    );
+    assert_eq!(
+        package.license_file,
+        Some(MaybeInherited::Local("../../LICENSE".to_string()))
+    );
+}
+
+#[test]
+fn missing_workspace_package_table() {
+    let workspace = Manifest::from_str("[workspace]\nmembers = [\"bar\"]").unwrap();
+    let mut m = Manifest::from_str(
+        r#"
+[package]
+name = "bar"
+version.workspace = true
+"#,
+    )
+    .unwrap();
+
+    let err = m
+        .inherit_from(workspace.workspace.as_ref().unwrap(), Path::new(".."))
+        .unwrap_err();
+    assert!(
+        matches!(err, Error::MissingWorkspaceKeys { ref keys } if keys == &["package.version"])
+    );
+}
+
+const WORKSPACE_DEPENDENCIES: &str = r#"
+[workspace]
+members = ["crates/*"]
+
+[workspace.dependencies]
+chrono = "0.4"
+serde = { version = "1.0", features = ["derive"], default-features = false }
+local = { path = "crates/local", version = "0.1" }
+"#;
+
+#[test]
+fn dependencies() {
+    let workspace = Manifest::from_str(WORKSPACE_DEPENDENCIES).unwrap();
+    let mut m = Manifest::from_str(
+        r#"
+[package]
+name = "bar"
+version = "0.1.0"
+
+[dependencies]
+chrono.workspace = true
+serde = { workspace = true, features = ["rc", "derive"], optional = true }
+config = "0.13"
+
+[dev-dependencies]
+local.workspace = true
+
+[target.'cfg(unix)'.build-dependencies]
+chrono = { workspace = true, features = ["clock"] }
+"#,
+    )
+    .unwrap();
+
+    m.inherit_dependencies_from(workspace.workspace.as_ref().unwrap(), Path::new("../.."))
+        .unwrap();
+
+    let deps = m.dependencies.as_ref().unwrap();
+    assert_eq!(deps["chrono"].req(), "0.4");
+    assert_eq!(deps["config"], Dependency::Simple("0.13".to_string()));
+
+    let serde = deps["serde"].detail().unwrap();
+    assert_eq!(serde.version.as_deref(), Some("1.0"));
+    assert_eq!(serde.features, Some(vec!["derive".into(), "rc".into()]));
+    assert_eq!(serde.optional, Some(true));
+    assert_eq!(serde.default_features, Some(false));
+
+    let local = m.dev_dependencies.as_ref().unwrap()["local"].detail();
+    assert_eq!(local.unwrap().path.as_deref(), Some("../../crates/local"));
+
+    let target = &m.target.as_ref().unwrap()["cfg(unix)"];
+    let chrono = target.build_dependencies["chrono"].detail().unwrap();
+    assert_eq!(chrono.version.as_deref(), Some("0.4"));
+    assert_eq!(chrono.features, Some(vec!["clock".into()]));
+}
+
+#[test]
+fn public_and_path_base_dependencies() {
+    let workspace = Manifest::from_str(
+        r#"
+[workspace]
+members = ["bar"]
+
+[workspace.dependencies]
+serde = "1.0"
+local = { path = "local", base = "libs" }
+"#,
+    )
+    .unwrap();
+    let mut m = Manifest::from_str(
+        r#"
+[package]
+name = "bar"
+version = "0.1.0"
+
+[dependencies]
+serde = { workspace = true, public = true }
+local.workspace = true
+"#,
+    )
+    .unwrap();
+
+    m.inherit_dependencies_from(workspace.workspace.as_ref().unwrap(), Path::new(".."))
+        .unwrap();
+
+    let deps = m.dependencies.as_ref().unwrap();
+    assert_eq!(deps["serde"].detail().unwrap().public, Some(true));
+    let local = deps["local"].detail().unwrap();
+    assert_eq!(local.path.as_deref(), Some("local"));
+    assert_eq!(local.base.as_deref(), Some("libs"));
+}
+
+#[test]
+fn dependency_default_features() {
+    let workspace = Manifest::from_str(WORKSPACE_DEPENDENCIES).unwrap();
+    let mut m = Manifest::from_str(
+        r#"
+[package]
+name = "bar"
+version = "0.1.0"
+
+[dependencies]
+serde = { workspace = true, default-features = true }
+chrono = { workspace = true, default-features = false }
+"#,
+    )
+    .unwrap();
+
+    m.inherit_dependencies_from(workspace.workspace.as_ref().unwrap(), Path::new(".."))
+        .unwrap();
+
+    // The member can turn the default features back on, but not off
+    let deps = m.dependencies.as_ref().unwrap();
+    assert_eq!(deps["serde"].detail().unwrap().default_features, Some(true));
+    assert_eq!(deps["chrono"].detail().unwrap().default_features, None);
+}
+
+#[test]
+fn dependency_extra_keys() {
+    let workspace = Manifest::from_str(WORKSPACE_DEPENDENCIES).unwrap();
+    let mut m = Manifest::from_str(
+        r#"
+[package]
+name = "bar"
+version = "0.1.0"
+
+[dependencies]
+serde = { workspace = true, new-key = "value" }
+"#,
+    )
+    .unwrap();
+
+    m.inherit_dependencies_from(workspace.workspace.as_ref().unwrap(), Path::new(".."))
+        .unwrap();
+
+    let serde = m.dependencies.as_ref().unwrap()["serde"].detail().unwrap();
+    assert_eq!(serde.extra["new-key"].as_str(), Some("value"));
+}
+
+#[test]
+fn dependency_workspace_only_keys() {
+    let workspace = Manifest::from_str(WORKSPACE_DEPENDENCIES).unwrap();
+    let mut m = Manifest::from_str(
+        r#"
+[package]
+name = "bar"
+version = "0.1.0"
+
+[dependencies]
+serde = { workspace = true, package = "serde_json" }
+"#,
+    )
+    .unwrap();
+
+    let err = m
+        .inherit_dependencies_from(workspace.workspace.as_ref().unwrap(), Path::new(".."))
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        Error::InvalidWorkspaceInheritance { ref key } if key == "dependencies.serde.package"
+    ));
+}
+
+#[test]
+fn missing_dependency() {
+    let workspace = Manifest::from_str(WORKSPACE_DEPENDENCIES).unwrap();
+    let mut m = Manifest::from_str(
+        r#"
+[package]
+name = "bar"
+version = "0.1.0"
+
+[target.'cfg(windows)'.dependencies]
+winapi.workspace = true
+"#,
+    )
+    .unwrap();
+
+    let err = m
+        .inherit_dependencies_from(workspace.workspace.as_ref().unwrap(), Path::new(".."))
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        Error::MissingWorkspaceKey { ref key } if key == "target.\"cfg(windows)\".dependencies.winapi"
+    ));
+}
+
+const WORKSPACE_LINTS: &str = r#"
+[workspace]
+members = ["bar"]
+
+[workspace.lints.rust]
+unsafe_code = "forbid"
+unused = { level = "allow", priority = -1 }
+
+[workspace.lints.clippy]
+pedantic = { level = "warn", priority = -1 }
+all = "deny"
+"#;
+
+#[test]
+fn inherited_lints() {
+    let workspace = Manifest::from_str(WORKSPACE_LINTS).unwrap();
+    let m = Manifest::from_str(
+        r#"
+[package]
+name = "bar"
+version = "0.1.0"
+
+[lints]
+workspace = true
+"#,
+    )
+    .unwrap();
+
+    let lints = m
+        .effective_lints(workspace.workspace.as_ref().unwrap())
+        .unwrap()
+        .into_iter()
+        .map(|lint| (lint.qualified_name(), lint.level, lint.priority))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        lints,
+        vec![
+            ("clippy::pedantic".to_string(), LintLevel::Warn, -1),
+            ("unused".to_string(), LintLevel::Allow, -1),
+            ("clippy::all".to_string(), LintLevel::Deny, 0),
+            ("unsafe_code".to_string(), LintLevel::Forbid, 0),
+        ]
+    );
+}
+
+#[test]
+fn local_lints() {
+    let workspace = Manifest::from_str(WORKSPACE_LINTS).unwrap();
+    let m = Manifest::from_str(
+        r#"
+[package]
+name = "bar"
+version = "0.1.0"
+
+[lints.rust]
+missing_docs = "warn"
+"#,
+    )
+    .unwrap();
+
+    let lints = m
+        .effective_lints(workspace.workspace.as_ref().unwrap())
+        .unwrap();
+    assert_eq!(lints.len(), 1);
+    assert_eq!(lints[0].qualified_name(), "missing_docs");
+}
+
+#[test]
+fn inherited_and_local_lints() {
+    let workspace = Manifest::from_str(WORKSPACE_LINTS).unwrap();
+    let m = Manifest::from_str(
+        r#"
+[package]
+name = "bar"
+version = "0.1.0"
+
+[lints]
+workspace = true
+rust.unused = "forbid"
+"#,
+    )
+    .unwrap();
+
+    let err = m
+        .effective_lints(workspace.workspace.as_ref().unwrap())
+        .unwrap_err();
+    assert!(matches!(err, Error::InvalidWorkspaceInheritance { ref key } if key == "lints"));
+
+    let workspace = Manifest::from_str("[workspace]\nmembers = [\"bar\"]").unwrap();
+    let m = Manifest::from_str("[package]\nname = \"bar\"\n[lints]\nworkspace = true").unwrap();
+    let err = m
+        .effective_lints(workspace.workspace.as_ref().unwrap())
+        .unwrap_err();
+    assert!(matches!(err, Error::MissingWorkspaceKey { ref key } if key == "lints"));
+}
@@ -331,3 +331,391 @@ tokio = { workspace = true, features = [ "rt-multi-thread" ] }
     .unwrap();
     insta::assert_debug_snapshot!(m);
 }
+
+/// Parse errors point at the offending key and its position in the manifest.
+#[test]
+fn error_location() {
+    let err = Manifest::from_str(
+        r#"
+[package]
+name = "foo"
+version = "1"
+
+[profile.release]
+strip = "everything"
+"#,
+    )
+    .unwrap_err();
+    let location = err.location().unwrap();
+    assert_eq!(location.key.as_deref(), Some("profile.release.strip"));
+    assert_eq!((location.line, location.column), (Some(7), Some(1)));
+    assert!(matches!(
+        err.without_location(),
+        lib::Error::InvalidStripSetting(value) if value == "everything"
+    ));
+
+    let err = Manifest::from_str(
+        r#"
+[package]
+name = "foo"
+
+[profile.release.package."serde@1.0"]
+opt-level = "3"
+"#,
+    )
+    .unwrap_err();
+    let location = err.location().unwrap();
+    assert_eq!(
+        location.key.as_deref(),
+        Some("profile.release.package.\"serde@1.0\".opt-level")
+    );
+    assert_eq!(location.line, Some(6));
+    assert!(matches!(err.without_location(), lib::Error::InvalidOptLevel(value) if value == "3"));
+
+    let err = Manifest::from_str(
+        r#"
+[package]
+name = "foo"
+
+[target.'cfg(unix)'.dependencies]
+bar = { version = "1", artifact = "exe" }
+"#,
+    )
+    .unwrap_err();
+    let location = err.location().unwrap();
+    assert_eq!(
+        location.key.as_deref(),
+        Some("target.\"cfg(unix)\".dependencies.bar.artifact")
+    );
+    assert_eq!((location.line, location.column), (Some(6), Some(24)));
+    assert!(
+        matches!(err.without_location(), lib::Error::InvalidArtifactKind(value) if value == "exe")
+    );
+
+    let err = Manifest::from_str(
+        r#"
+[package]
+name = "foo"
+
+[dependencies]
+serde = { version = "1", features = "derive" }
+"#,
+    )
+    .unwrap_err();
+    let location = err.location().unwrap();
+    assert_eq!(location.key.as_deref(), Some("dependencies.serde"));
+    assert_eq!(location.line, Some(6));
+
+    let err = Manifest::from_str("[package]\nname = \"foo\"\n\n[[bin]]\nname = 1\n").unwrap_err();
+    let location = err.location().unwrap();
+    assert_eq!(location.key.as_deref(), Some("bin.0.name"));
+    assert_eq!(location.line, Some(5));
+
+    let err = Manifest::from_str("[package]\nname = \"foo\"\nversion = \n").unwrap_err();
+    let location = err.location().unwrap();
+    assert_eq!(location.line, Some(3));
+}
+
+#[test]
+fn error_location_in_file() {
+    let tempdir = utils::prepare("[package]\nname = \"foo\"\nedition = 2021\n", vec![]);
+    let path = tempdir.path().join("Cargo.toml");
+    let err = Manifest::from_path(&path).unwrap_err();
+    let location = err.location().unwrap();
+    assert_eq!(location.path.as_deref(), Some(path.as_path()));
+    assert_eq!(location.key.as_deref(), Some("package.edition"));
+    assert_eq!((location.line, location.column), (Some(3), Some(11)));
+}
+
+#[test]
+fn invalid_strip_setting() {
+    let err = lib::StripSetting::try_from(lib::Value::String("all".into())).unwrap_err();
+    assert!(matches!(err, lib::Error::InvalidStripSetting(ref value) if value == "all"));
+
+    let err = lib::StripSetting::try_from(lib::Value::Integer(1)).unwrap_err();
+    assert!(matches!(err, lib::Error::InvalidStripSetting(ref value) if value == "1"));
+}
+
+#[test]
+fn unknown_keys() {
+    let (m, unknown_keys) = Manifest::from_slice_with_unknown_keys(
+        br#"
+[package]
+name = "foo"
+version = "0.1.0"
+default_run = "foo"
+autobin = false
+
+[package.metadata.docs]
+all-features = true
+
+[dev-dependancies]
+bar = "1.0"
+
+[[bin]]
+name = "foo"
+pth = "src/foo.rs"
+
+[target.'cfg(unix)'.dependencies]
+baz = { version = "1.0", optinal = true }
+
+[profile.release]
+lto = true
+optlevel = 3
+
+[lints.rust]
+unexpected_cfgs = { level = "warn", check-cfg = ["cfg(has_foo)"], check_cfg = [] }
+
+[badges]
+new-ci = { repository = "foo/bar" }
+maintenance = { status = "experimental", new-key = 1 }
+"#,
+    )
+    .unwrap();
+    assert_eq!(m.package.as_ref().unwrap().name, "foo");
+    assert_eq!(
+        unknown_keys,
+        vec![
+            "dev-dependancies",
+            "package.autobin",
+            "package.default_run",
+            "target.\"cfg(unix)\".dependencies.baz.optinal",
+            "bin.0.pth",
+            "profile.release.optlevel",
+            "badges.new-ci",
+            "badges.maintenance.new-key",
+            "lints.rust.unexpected_cfgs.check_cfg",
+        ]
+    );
+    let lints = &m.lints.unwrap().lints["rust"];
+    let lib::Lint::Config(config) = &lints["unexpected_cfgs"] else {
+        panic!("expected a lint config");
+    };
+    assert_eq!(config.check_cfg, Some(vec!["cfg(has_foo)".to_string()]));
+
+    let (_, unknown_keys) =
+        Manifest::from_slice_with_unknown_keys(&read("Cargo.toml").unwrap()).unwrap();
+    assert!(unknown_keys.is_empty());
+}
+
+#[test]
+fn unknown_keys_legacy() {
+    let (m, unknown_keys) = Manifest::from_slice_with_unknown_keys(
+        br#"
+name = "foo"
+version = "0.1.0"
+authros = ["Jane Doe"]
+
+[dependencies]
+bar = "1.0"
+"#,
+    )
+    .unwrap();
+    assert_eq!(m.package.unwrap().name, "foo");
+    assert_eq!(unknown_keys, vec!["authros"]);
+
+    let (m, unknown_keys) = Manifest::from_slice_with_unknown_keys(
+        br#"
+[project]
+name = "foo"
+version = "0.1.0"
+autobin = false
+"#,
+    )
+    .unwrap();
+    assert_eq!(m.package.unwrap().name, "foo");
+    assert_eq!(unknown_keys, vec!["project.autobin"]);
+}
+
+#[test]
+fn profile_settings() {
+    let m = Manifest::from_str(
+        r#"
+[package]
+name = "foo"
+version = "1"
+
+[profile.dev]
+opt-level = "s"
+debug = "line-tables-only"
+lto = "thin"
+panic = "abort"
+split-debuginfo = "packed"
+
+[profile.release]
+opt-level = 3
+debug = 1
+lto = false
+
+[profile.release.build-override]
+opt-level = 0
+debug = true
+"#,
+    )
+    .unwrap();
+
+    let profiles = m.profile.unwrap();
+    let dev = profiles.dev.unwrap();
+    assert_eq!(dev.opt_level, Some(lib::OptLevel::S));
+    assert_eq!(dev.debug.as_deref(), Some(&lib::DebugInfo::LineTablesOnly));
+    assert_eq!(dev.lto.as_deref(), Some(&lib::Lto::Thin));
+    assert_eq!(dev.panic, Some(lib::PanicStrategy::Abort));
+    assert_eq!(dev.split_debuginfo, Some(lib::SplitDebuginfo::Packed));
+
+    let release = profiles.release.unwrap();
+    assert_eq!(release.opt_level, Some(lib::OptLevel::O3));
+    assert_eq!(release.debug.as_deref(), Some(&lib::DebugInfo::Limited));
+    assert_eq!(release.lto.as_deref(), Some(&lib::Lto::ThinLocal));
+
+    let build_override = release.build_override.unwrap();
+    assert_eq!(build_override.opt_level, Some(lib::OptLevel::O0));
+    assert_eq!(build_override.debug.as_deref(), Some(&lib::DebugInfo::Full));
+}
+
+#[test]
+fn invalid_profile_settings() {
+    let err = lib::OptLevel::try_from(lib::Value::Integer(4)).unwrap_err();
+    assert!(matches!(err, lib::Error::InvalidOptLevel(ref value) if value == "4"));
+
+    // Like Cargo, numeric levels must not be quoted
+    let err = lib::OptLevel::try_from(lib::Value::String("3".into())).unwrap_err();
+    assert!(matches!(err, lib::Error::InvalidOptLevel(ref value) if value == "3"));
+
+    let err = lib::DebugInfo::try_from(lib::Value::String("most".into())).unwrap_err();
+    assert!(matches!(err, lib::Error::InvalidDebugInfo(ref value) if value == "most"));
+
+    let err = lib::Lto::try_from(lib::Value::Integer(1)).unwrap_err();
+    assert!(matches!(err, lib::Error::InvalidLto(ref value) if value == "1"));
+
+    let err = Manifest::from_str("[package]\nname = \"foo\"\n[profile.dev]\npanic = \"crash\"");
+    assert!(err.is_err());
+}
+
+#[test]
+fn replace_hints_and_unstable_keys() {
+    let (m, unknown_keys) = Manifest::from_slice_with_unknown_keys(
+        br#"
+cargo-features = ["metabuild", "per-package-target", "test-dummy-unstable"]
+
+[package]
+name = "foo"
+version = "0.1.0"
+metabuild = ["bar", "baz"]
+im-a-teapot = true
+forced-target = "wasm32-unknown-unknown"
+default-target = "x86_64-unknown-linux-gnu"
+
+[replace]
+"bar:0.1.0" = { git = "https://github.com/foo/bar" }
+"baz@1.0.0" = { path = "../baz" }
+
+[hints]
+mostly-unused = true
+"#,
+    )
+    .unwrap();
+    assert!(unknown_keys.is_empty(), "{unknown_keys:?}");
+
+    let package = m.package.as_ref().unwrap();
+    assert_eq!(
+        package.metabuild,
+        Some(lib::StringOrVec::Vec(vec![
+            "bar".to_string(),
+            "baz".to_string()
+        ]))
+    );
+    assert_eq!(package.im_a_teapot, Some(true));
+    assert_eq!(
+        package.forced_target.as_deref(),
+        Some("wasm32-unknown-unknown")
+    );
+    assert_eq!(
+        package.default_target.as_deref(),
+        Some("x86_64-unknown-linux-gnu")
+    );
+
+    let replace = m.replace.as_ref().unwrap();
+    assert_eq!(
+        replace.keys().collect::<Vec<_>>(),
+        ["bar:0.1.0", "baz@1.0.0"]
+    );
+    let replacements = m.replacements().unwrap();
+    let (bar, dependency) = &replacements[0];
+    assert_eq!(bar.to_string(), "bar@0.1.0");
+    assert!(bar.matches("bar", "0.1.0"));
+    assert_eq!(dependency.git(), Some("https://github.com/foo/bar"));
+
+    assert_eq!(m.hints.unwrap().mostly_unused, Some(true));
+
+    let m = Manifest::from_str("[package]\nname = \"foo\"\n[replace]\n\"bar@\" = \"1.0\"").unwrap();
+    let err = m.replacements().unwrap_err();
+    assert!(matches!(err, lib::Error::InvalidPackageSpec { ref spec, .. } if spec == "bar@"));
+}
+
+#[test]
+fn artifact_dependencies() {
+    let (m, unknown_keys) = Manifest::from_slice_with_unknown_keys(
+        br#"
+cargo-features = ["bindeps", "public-dependency", "path-bases"]
+
+[package]
+name = "foo"
+version = "0.1.0"
+
+[dependencies]
+tool = { version = "1.0", artifact = "bin:tool", target = "target" }
+plugin = { path = "../plugin", artifact = ["cdylib", "staticlib"], lib = true }
+all = { version = "1.0", artifact = "bin" }
+api = { version = "1.0", public = true }
+local = { path = "local", base = "libs" }
+"#,
+    )
+    .unwrap();
+    assert!(unknown_keys.is_empty(), "{unknown_keys:?}");
+
+    let deps = m.dependencies.unwrap();
+    let detail = |name: &str| deps[name].detail().unwrap().clone();
+    assert_eq!(
+        detail("tool").artifact,
+        Some(lib::ArtifactKinds(vec![lib::ArtifactKind::SelectedBinary(
+            "tool".to_string()
+        )]))
+    );
+    assert_eq!(detail("tool").target.as_deref(), Some("target"));
+    assert_eq!(
+        detail("plugin").artifact,
+        Some(lib::ArtifactKinds(vec![
+            lib::ArtifactKind::Cdylib,
+            lib::ArtifactKind::Staticlib
+        ]))
+    );
+    assert_eq!(detail("plugin").lib, Some(true));
+    assert_eq!(
+        detail("all").artifact,
+        Some(lib::ArtifactKinds(vec![lib::ArtifactKind::AllBinaries]))
+    );
+    assert_eq!(detail("api").public, Some(true));
+    assert_eq!(detail("local").base.as_deref(), Some("libs"));
+
+    // None of these can be simplified to a version string
+    for (name, dep) in deps {
+        assert!(dep.simplify().detail().is_some(), "{name}");
+    }
+
+    // Dependencies are untagged, so the error only says that the dependency is invalid
+    let res = Manifest::from_str(
+        r#"
+[package]
+name = "foo"
+
+[dependencies]
+tool = { version = "1.0", artifact = "bin:" }
+"#,
+    );
+    assert!(res.is_err());
+    let err = lib::ArtifactKind::from_str("bin:").unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "'bin:' is not a valid value for 'artifact'"
+    );
+}
@@ -0,0 +1,400 @@
+use cargo_manifest::{
+    DebugInfo, Error, GitReference, Lto, Manifest, OptLevel, Package, PackageIdSpec, PanicStrategy,
+    ProfilePackageSpec, Profiles, ResolvedProfile, SourceKind, StripSetting,
+};
+use std::str::FromStr;
+
+fn profiles(profiles: &str) -> Profiles {
+    let manifest = format!("[package]\nname = \"foo\"\n{profiles}");
+    Manifest::from_str(&manifest).unwrap().profile.unwrap()
+}
+
+#[test]
+fn builtin_defaults() {
+    let profiles = Profiles::default();
+
+    let dev = profiles.resolve("dev").unwrap();
+    assert_eq!(dev, ResolvedProfile::dev());
+    assert_eq!(dev.opt_level, OptLevel::O0);
+    assert_eq!(dev.codegen_units, 256);
+    assert!(dev.incremental);
+
+    let release = profiles.resolve("release").unwrap();
+    assert_eq!(release.opt_level, OptLevel::O3);
+    assert_eq!(release.codegen_units, 16);
+    assert_eq!(release.strip, StripSetting::Debuginfo);
+
+    let test = profiles.resolve("test").unwrap();
+    assert_eq!(test.name, "test");
+    assert_eq!(test.opt_level, OptLevel::O0);
+
+    let bench = profiles.resolve("bench").unwrap();
+    assert_eq!(bench.name, "bench");
+    assert_eq!(bench.opt_level, OptLevel::O3);
+}
+
+#[test]
+fn inherits_chain() {
+    let profiles = profiles(
+        r#"
+[profile.release]
+lto = "thin"
+debug = "limited"
+
+[profile.ci]
+inherits = "release"
+panic = "abort"
+
+[profile.ci-fast]
+inherits = "ci"
+opt-level = 1
+codegen-units = 256
+
+[profile.bench]
+debug = false
+"#,
+    );
+
+    let resolved = profiles.resolve("ci-fast").unwrap();
+    assert_eq!(resolved.name, "ci-fast");
+    assert_eq!(resolved.opt_level, OptLevel::O1);
+    assert_eq!(resolved.codegen_units, 256);
+    assert_eq!(resolved.panic, PanicStrategy::Abort);
+    assert_eq!(resolved.lto, Lto::Thin);
+    assert_eq!(resolved.debug, DebugInfo::Limited);
+    assert_eq!(resolved.strip, StripSetting::None);
+    assert!(!resolved.debug_assertions);
+
+    let bench = profiles.resolve("bench").unwrap();
+    assert_eq!(bench.lto, Lto::Thin);
+    assert_eq!(bench.debug, DebugInfo::None);
+    assert_eq!(bench.strip, StripSetting::Debuginfo);
+}
+
+#[test]
+fn invalid_inherits() {
+    let profiles = profiles(
+        r#"
+[profile.a]
+inherits = "b"
+
+[profile.b]
+inherits = "a"
+
+[profile.c]
+opt-level = 2
+
+[profile.d]
+inherits = "missing"
+"#,
+    );
+
+    let err = profiles.resolve("a").unwrap_err();
+    assert!(
+        matches!(err, Error::ProfileInheritanceCycle { ref chain } if chain == &["a", "b", "a"])
+    );
+    assert_eq!(
+        err.to_string(),
+        "profile inheritance loop detected: a -> b -> a"
+    );
+
+    let err = profiles.resolve("c").unwrap_err();
+    assert!(matches!(err, Error::MissingProfileInherits { ref name } if name == "c"));
+
+    let err = profiles.resolve("d").unwrap_err();
+    assert!(matches!(err, Error::UnknownProfile { ref name } if name == "missing"));
+
+    let err = profiles.resolve("nope").unwrap_err();
+    assert!(matches!(err, Error::UnknownProfile { ref name } if name == "nope"));
+}
+
+#[test]
+fn package_overrides() {
+    let profiles = profiles(
+        r#"
+[profile.dev.package."*"]
+opt-level = 2
+debug = false
+
+[profile.dev.package.image]
+opt-level = 3
+
+[profile.dev.package."image@0.24"]
+codegen-units = 1
+
+[profile.dev.build-override]
+opt-level = 1
+
+[profile.ci]
+inherits = "dev"
+
+[profile.ci.package.image]
+debug = "limited"
+"#,
+    );
+
+    let dev = profiles.dev.as_ref().unwrap();
+    assert_eq!(
+        dev.package
+            .keys()
+            .map(|spec| spec.to_string())
+            .collect::<Vec<_>>(),
+        vec!["*", "image", "image@0.24"]
+    );
+    assert_eq!(
+        dev.build_override.as_ref().unwrap().opt_level,
+        Some(OptLevel::O1)
+    );
+
+    let serde = dev.for_package("serde", "1.0.200", false);
+    assert_eq!(serde.opt_level, Some(OptLevel::O2));
+    assert_eq!(serde.debug.as_deref(), Some(&DebugInfo::None));
+    assert!(serde.package.is_empty());
+
+    let member = dev.for_package("serde", "1.0.200", true);
+    assert_eq!(member.opt_level, None);
+
+    let image = dev.for_package("image", "0.24.9", false);
+    assert_eq!(image.opt_level, Some(OptLevel::O3));
+    assert_eq!(image.codegen_units, Some(1));
+
+    let image = dev.for_package("image", "0.25.0", false);
+    assert_eq!(image.codegen_units, None);
+
+    let resolved = profiles
+        .resolve_for_package("ci", "image", "0.25.0", false)
+        .unwrap();
+    assert_eq!(resolved.name, "ci");
+    assert_eq!(resolved.opt_level, OptLevel::O3);
+    assert_eq!(resolved.debug, DebugInfo::Limited);
+    assert_eq!(resolved.codegen_units, 256);
+
+    let resolved = profiles.resolve("ci").unwrap();
+    assert_eq!(resolved.opt_level, OptLevel::O0);
+}
+
+#[test]
+fn package_overrides_only_merge_settings() {
+    let profiles = profiles(
+        r#"
+[profile.dev]
+codegen-units = 4
+
+[profile.dev.package.image]
+opt-level = 3
+unknown-key = true
+"#,
+    );
+
+    let dev = profiles.dev.as_ref().unwrap();
+    let image = dev.for_package("image", "0.24.9", false);
+    assert_eq!(image.opt_level, Some(OptLevel::O3));
+    assert_eq!(image.codegen_units, Some(4));
+    assert!(image.extra.is_empty());
+}
+
+#[test]
+fn invalid_package_overrides() {
+    for (profile, key, which) in [
+        (
+            "[profile.dev.package.foo]\ninherits = \"release\"",
+            "inherits",
+            "package",
+        ),
+        (
+            "[profile.dev.package.foo.package.bar]\nopt-level = 1",
+            "package",
+            "package",
+        ),
+        (
+            "[profile.dev.package.foo.build-override]\nopt-level = 1",
+            "build-override",
+            "package",
+        ),
+        (
+            "[profile.dev.package.\"*\"]\npanic = \"abort\"",
+            "panic",
+            "package",
+        ),
+        (
+            "[profile.dev.build-override]\nlto = true",
+            "lto",
+            "build-override",
+        ),
+        (
+            "[profile.dev.build-override]\nrpath = true",
+            "rpath",
+            "build-override",
+        ),
+    ] {
+        let manifest = format!("[package]\nname = \"foo\"\n{profile}");
+        let err = Manifest::from_str(&manifest).unwrap_err();
+        let message = format!("`{key}` may not be specified in a `{which}` profile");
+        assert!(err.to_string().contains(&message), "{err}");
+    }
+}
+
+#[test]
+fn package_id_spec() {
+    let spec = PackageIdSpec::from_str("serde@1.0").unwrap();
+    assert_eq!(spec.name, "serde");
+    assert_eq!(spec.version.as_deref(), Some("1.0"));
+    assert!(spec.matches("serde", "1.0.200"));
+    assert!(!spec.matches("serde", "1.1.0"));
+    assert!(!spec.matches("serde_json", "1.0.0"));
+    assert_eq!(spec.to_string(), "serde@1.0");
+
+    assert_eq!(
+        ProfilePackageSpec::from_str("*").unwrap(),
+        ProfilePackageSpec::All
+    );
+
+    let err = PackageIdSpec::from_str("serde@").unwrap_err();
+    assert!(matches!(err, Error::InvalidPackageSpec { ref spec, .. } if spec == "serde@"));
+    for invalid in [
+        "serde@1.x",
+        "serde@1.2.3.4",
+        "serde@1.0-beta",
+        "serde@1.0.0-",
+    ] {
+        let err = PackageIdSpec::from_str(invalid).unwrap_err();
+        assert!(matches!(err, Error::InvalidPackageSpec { .. }), "{invalid}");
+    }
+
+    let err = Manifest::from_str("[package]\nname = \"foo\"\n[profile.dev.package.\"@1\"]\n");
+    assert!(err.is_err());
+}
+
+#[test]
+fn package_id_spec_pre_releases_and_build_metadata() {
+    let spec = |spec: &str| PackageIdSpec::from_str(spec).unwrap();
+
+    // Like in Cargo, pre-releases must be requested explicitly
+    assert!(!spec("foo").matches("bar", "1.0.0"));
+    assert!(spec("foo").matches("foo", "1.0.0-beta.1"));
+    assert!(!spec("foo@1").matches("foo", "1.0.0-beta.1"));
+    assert!(!spec("foo@1.0").matches("foo", "1.0.0-beta.1"));
+    assert!(!spec("foo@1.0.0").matches("foo", "1.0.0-beta.1"));
+    assert!(spec("foo@1.0.0-beta.1").matches("foo", "1.0.0-beta.1"));
+    assert!(!spec("foo@1.0.0-beta.1").matches("foo", "1.0.0-beta.10"));
+    assert!(!spec("foo@1.0.0-beta.1").matches("foo", "1.0.0"));
+    assert!(!spec("foo@1.0.0-beta").matches("foo", "1.0.0-beta.1"));
+
+    // Build metadata is only compared if the spec has one
+    assert!(spec("foo@1.0.0").matches("foo", "1.0.0+build.5"));
+    assert!(spec("foo@1.0").matches("foo", "1.0.3+build.5"));
+    assert!(spec("foo@1.0.0+abc").matches("foo", "1.0.0+abc"));
+    assert!(!spec("foo@1.0.0+abc").matches("foo", "1.0.0+def"));
+    assert!(!spec("foo@1.0.0+abc").matches("foo", "1.0.0"));
+    assert!(spec("foo@1.0.0-rc.1+abc").matches("foo", "1.0.0-rc.1+abc"));
+    assert!(!spec("foo@1.0.0-rc.1+abc").matches("foo", "1.0.0-rc.1"));
+    assert!(!spec("foo@1.0.0+abc").matches("foo", "1.0.0-rc.1+abc"));
+    assert!(PackageIdSpec::from_str("foo@1.0+abc").is_err());
+
+    // Components are compared as numbers, not as prefixes
+    assert!(!spec("foo@1.1").matches("foo", "1.10.0"));
+    assert!(!spec("foo@1").matches("foo", "10.0.0"));
+    assert!(!spec("foo@1.0").matches("foo", "1.0"));
+}
+
+#[test]
+fn package_id_spec_urls() {
+    let parse = |spec: &str| PackageIdSpec::from_str(spec).unwrap();
+
+    let spec = parse("registry+https://github.com/rust-lang/crates.io-index#serde@1.0");
+    assert_eq!(spec.name, "serde");
+    assert_eq!(spec.version.as_deref(), Some("1.0"));
+    assert_eq!(
+        spec.url.as_deref(),
+        Some("https://github.com/rust-lang/crates.io-index")
+    );
+    assert_eq!(spec.kind, Some(SourceKind::Registry));
+    assert!(spec.matches("serde", "1.0.200"));
+
+    let spec = parse("path+file:///path/to/foo#0.1.0");
+    assert_eq!(spec.name, "foo");
+    assert_eq!(spec.version.as_deref(), Some("0.1.0"));
+    assert_eq!(spec.url.as_deref(), Some("file:///path/to/foo"));
+    assert_eq!(spec.kind, Some(SourceKind::Path));
+
+    let spec = parse("git+https://github.com/rust-lang/cargo.git?branch=master#cargo-util@0.2");
+    assert_eq!(spec.name, "cargo-util");
+    assert_eq!(
+        spec.kind,
+        Some(SourceKind::Git(GitReference::Branch("master".to_string())))
+    );
+
+    let spec = parse("https://github.com/rust-lang/cargo#0.52.0");
+    assert_eq!(spec.name, "cargo");
+    assert_eq!(spec.kind, None);
+
+    let spec = parse("sparse+https://index.crates.io/#serde");
+    assert_eq!(spec.kind, Some(SourceKind::SparseRegistry));
+    assert_eq!(spec.url.as_deref(), Some("sparse+https://index.crates.io/"));
+
+    // Display omits the name if it can be inferred from the URL
+    for (input, display) in [
+        ("foo", "foo"),
+        ("foo:1.2.3", "foo@1.2.3"),
+        (
+            "path+file:///path/to/foo#0.1.0",
+            "path+file:///path/to/foo#0.1.0",
+        ),
+        (
+            "path+file:///path/to/foo#foo@0.1.0",
+            "path+file:///path/to/foo#0.1.0",
+        ),
+        (
+            "registry+https://github.com/rust-lang/crates.io-index#serde",
+            "registry+https://github.com/rust-lang/crates.io-index#serde",
+        ),
+        (
+            "git+https://github.com/rust-lang/cargo?rev=abc#cargo@0.52.0",
+            "git+https://github.com/rust-lang/cargo?rev=abc#0.52.0",
+        ),
+    ] {
+        let spec = parse(input);
+        assert_eq!(spec.to_string(), display);
+        assert_eq!(parse(display), spec);
+    }
+
+    let mut package = Package::<()>::new("foo".to_string(), "0.1.3".to_string());
+    assert!(parse("foo@0.1").matches_package(&package));
+    assert!(!parse("foo@0.2").matches_package(&package));
+    package.version = Some(cargo_manifest::MaybeInherited::inherited());
+    assert!(parse("foo").matches_package(&package));
+    assert!(!parse("foo@0.1").matches_package(&package));
+
+    for invalid in [
+        "",
+        "foo/bar",
+        "https://example.com",
+        "foo+https://example.com/foo",
+        "registry+https://example.com/index?branch=main#foo",
+        "git+https://example.com/foo?ref=main",
+    ] {
+        let err = PackageIdSpec::from_str(invalid).unwrap_err();
+        assert!(matches!(err, Error::InvalidPackageSpec { .. }), "{invalid}");
+    }
+
+    let manifest = Manifest::from_str(
+        r#"
+[package]
+name = "foo"
+
+[profile.dev.package."registry+https://github.com/rust-lang/crates.io-index#serde"]
+opt-level = 3
+
+[replace]
+"https://github.com/rust-lang/crates.io-index#bar:0.1.0" = { path = "../bar" }
+"#,
+    )
+    .unwrap();
+    let replacements = manifest.replacements().unwrap();
+    let (spec, _) = &replacements[0];
+    assert_eq!(spec.name, "bar");
+    assert!(spec.matches("bar", "0.1.0"));
+    let dev = manifest.profile.unwrap().dev.unwrap();
+    let dev = dev.for_package("serde", "1.0.0", false);
+    assert_eq!(dev.opt_level, Some(OptLevel::O3));
+}
@@ -1,4 +1,4 @@
-use cargo_manifest::{Lint, LintLevel, Manifest, MaybeInheritedLintsSet, Package};
+use cargo_manifest::{DebugInfo, Lint, LintLevel, Manifest, MaybeInheritedLintsSet, Package};
 
 #[test]
 fn basic() {
@@ -32,3 +32,146 @@ fn with_lints() {
     let serialized = toml::to_string(&manifest).unwrap();
     insta::assert_snapshot!(serialized);
 }
+
+#[test]
+fn unknown_keys_round_trip() {
+    let manifest = Manifest::from_slice(
+        br#"
+cargo-features = ["edition2027"]
+new-top-level-key = "kept"
+
+[package]
+name = "foo"
+version = "1.0.0"
+new-package-key = { nested = true }
+
+[dependencies]
+bar = { version = "1.0", new-dependency-key = 1 }
+baz = { workspace = true, new-dependency-key = 2 }
+
+[[bin]]
+name = "foo"
+new-product-key = false
+
+[profile.release]
+new-profile-key = "yes"
+
+[lints.rust]
+unexpected_cfgs = { level = "warn", check-cfg = ["cfg(has_foo)"], new-lint-key = 0.5 }
+
+[badges]
+new-ci = { repository = "foo/bar" }
+travis-ci = { repository = "foo/bar", new-badge-key = "x" }
+maintenance = { status = "experimental", new-key = 1 }
+"#,
+    )
+    .unwrap();
+
+    let serialized = toml::to_string(&manifest).unwrap();
+    insta::assert_snapshot!(serialized);
+
+    let reparsed = Manifest::from_slice(serialized.as_bytes()).unwrap();
+    assert_eq!(manifest, reparsed);
+}
+
+#[test]
+fn profile_settings_round_trip() {
+    let manifest = Manifest::from_slice(
+        br#"
+[package]
+name = "foo"
+version = "1.0.0"
+
+[profile.dev]
+opt-level = "z"
+debug = "line-directives-only"
+lto = "off"
+panic = "unwind"
+split-debuginfo = "off"
+
+[profile.release]
+opt-level = 2
+debug = 1
+lto = true
+strip = "debuginfo"
+
+[profile.release.build-override]
+opt-level = 0
+
+[profile.bench]
+debug = 2
+lto = "fat"
+
+[profile.test]
+debug = "full"
+
+[profile.ci]
+inherits = "release"
+debug = "limited"
+"#,
+    )
+    .unwrap();
+
+    // Different spellings of the same setting are equal, but serialized as written
+    let profiles = manifest.profile.as_ref().unwrap();
+    let (bench, test) = (profiles.bench.as_ref(), profiles.test.as_ref());
+    assert_eq!(bench.unwrap().debug, test.unwrap().debug);
+    assert_eq!(test.unwrap().debug.as_deref(), Some(&DebugInfo::Full));
+
+    let serialized = toml::to_string(&manifest).unwrap();
+    insta::assert_snapshot!(serialized);
+
+    let reparsed = Manifest::from_slice(serialized.as_bytes()).unwrap();
+    assert_eq!(manifest, reparsed);
+}
+
+#[test]
+fn replace_and_hints_round_trip() {
+    let manifest = Manifest::from_slice(
+        br#"
+cargo-features = ["metabuild"]
+
+[package]
+name = "foo"
+version = "1.0.0"
+metabuild = "bar"
+
+[replace]
+"bar:0.1.0" = { path = "../bar" }
+
+[hints]
+mostly-unused = false
+"#,
+    )
+    .unwrap();
+
+    let serialized = toml::to_string(&manifest).unwrap();
+    insta::assert_snapshot!(serialized);
+
+    let reparsed = Manifest::from_slice(serialized.as_bytes()).unwrap();
+    assert_eq!(manifest, reparsed);
+}
+
+#[test]
+fn artifact_dependencies_round_trip() {
+    let manifest = Manifest::from_slice(
+        br#"
+[package]
+name = "foo"
+version = "1.0.0"
+
+[dependencies]
+tool = { version = "1.0", artifact = "bin:tool", target = "target" }
+plugin = { version = "1.0", artifact = ["cdylib", "staticlib"], lib = true }
+api = { version = "1.0", public = true }
+local = { path = "local", base = "libs" }
+"#,
+    )
+    .unwrap();
+
+    let serialized = toml::to_string(&manifest).unwrap();
+    insta::assert_snapshot!(serialized);
+
+    let reparsed = Manifest::from_slice(serialized.as_bytes()).unwrap();
+    assert_eq!(manifest, reparsed);
+}
@@ -0,0 +1,73 @@
+#![cfg(feature = "semver")]
+
+use cargo_manifest::semver::{Version, VersionReq};
+use cargo_manifest::{Error, Manifest, MaybeInherited};
+use std::str::FromStr;
+
+#[test]
+fn version_req() {
+    let manifest = Manifest::from_str(
+        r#"
+[package]
+name = "foo"
+version = "1.2.3-beta.1"
+
+[dependencies]
+bare = "1.2"
+caret = "^1.2"
+exact = "=0.3.1"
+range = ">= 1.0, < 3"
+any = { git = "https://github.com/foo/bar" }
+inherited = { workspace = true }
+broken = "1.2.3.4"
+"#,
+    )
+    .unwrap();
+
+    let package = manifest.package.as_ref().unwrap();
+    assert_eq!(
+        package.semver_version().unwrap(),
+        MaybeInherited::Local(Version::parse("1.2.3-beta.1").unwrap())
+    );
+
+    let deps = manifest.dependencies.as_ref().unwrap();
+    let req = |name: &str| deps[name].version_req().unwrap();
+    assert_eq!(req("bare"), req("caret"));
+    assert!(req("bare").matches(&Version::new(1, 9, 0)));
+    assert!(!req("bare").matches(&Version::new(2, 0, 0)));
+    assert!(req("exact").matches(&Version::new(0, 3, 1)));
+    assert!(!req("exact").matches(&Version::new(0, 3, 2)));
+    assert!(req("range").matches(&Version::new(2, 5, 0)));
+    assert_eq!(req("any"), VersionReq::STAR);
+
+    let err = deps["inherited"].version_req().unwrap_err();
+    assert!(matches!(err, Error::InheritedVersionReq));
+
+    let err = deps["broken"].version_req().unwrap_err();
+    assert!(matches!(err, Error::InvalidVersionReq { ref req, .. } if req == "1.2.3.4"));
+    assert!(err
+        .to_string()
+        .starts_with("failed to parse the version requirement `1.2.3.4`"));
+}
+
+#[test]
+fn package_version() {
+    let manifest = Manifest::from_str("[package]\nname = \"foo\"\n").unwrap();
+    let package = manifest.package.unwrap();
+    assert_eq!(
+        package.semver_version().unwrap(),
+        MaybeInherited::Local(Version::new(0, 0, 0))
+    );
+
+    let manifest =
+        Manifest::from_str("[package]\nname = \"foo\"\nversion.workspace = true\n").unwrap();
+    let package = manifest.package.unwrap();
+    assert_eq!(
+        package.semver_version().unwrap(),
+        MaybeInherited::inherited()
+    );
+
+    let manifest = Manifest::from_str("[package]\nname = \"foo\"\nversion = \"1.0\"\n").unwrap();
+    let err = manifest.package.unwrap().semver_version().unwrap_err();
+    assert!(matches!(err, Error::InvalidVersion { ref version, .. } if version == "1.0"));
+}
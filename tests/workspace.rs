@@ -0,0 +1,271 @@
+use cargo_manifest::{
+    find_workspace_root, Error, Filesystem, Value, WorkspaceLoader, WorkspaceMembership,
+};
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+fn prepare(files: &[(&str, &str)]) -> TempDir {
+    let tempdir = tempfile::tempdir().unwrap();
+    for (path, content) in files {
+        let path = tempdir.path().join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, content).unwrap();
+    }
+    tempdir
+}
+
+fn find(tempdir: &TempDir, manifest_path: &str) -> Result<WorkspaceMembership, Error> {
+    find_workspace_root(&Filesystem::new(tempdir.path()), Path::new(manifest_path))
+}
+
+const PACKAGE: &str = "[package]\nname = \"foo\"\nversion = \"0.1.0\"\n";
+
+#[test]
+fn root_and_virtual() {
+    let tempdir = prepare(&[
+        ("Cargo.toml", "[workspace]\nmembers = [\"crates/*\"]"),
+        (
+            "root/Cargo.toml",
+            "[package]\nname = \"root\"\n[workspace]\nmembers = [\"a\"]",
+        ),
+    ]);
+    assert_eq!(
+        find(&tempdir, "Cargo.toml").unwrap(),
+        WorkspaceMembership::Virtual
+    );
+    assert_eq!(
+        find(&tempdir, "root/Cargo.toml").unwrap(),
+        WorkspaceMembership::Root
+    );
+}
+
+#[test]
+fn glob_member() {
+    let tempdir = prepare(&[
+        ("Cargo.toml", "[workspace]\nmembers = [\"crates/*\"]"),
+        ("crates/foo/Cargo.toml", PACKAGE),
+    ]);
+    assert_eq!(
+        find(&tempdir, "crates/foo/Cargo.toml").unwrap(),
+        WorkspaceMembership::Member {
+            root_manifest_path: PathBuf::from("Cargo.toml")
+        }
+    );
+}
+
+#[test]
+fn path_dependency_member() {
+    let tempdir = prepare(&[
+        (
+            "Cargo.toml",
+            "[package]\nname = \"root\"\n[workspace]\n[dependencies]\nfoo = { path = \"./libs/foo\" }",
+        ),
+        ("libs/foo/Cargo.toml", PACKAGE),
+    ]);
+    assert_eq!(
+        find(&tempdir, "libs/foo/Cargo.toml").unwrap(),
+        WorkspaceMembership::Member {
+            root_manifest_path: PathBuf::from("Cargo.toml")
+        }
+    );
+}
+
+#[test]
+fn not_a_member() {
+    let tempdir = prepare(&[
+        ("Cargo.toml", "[workspace]\nmembers = [\"crates/*\"]"),
+        ("other/foo/Cargo.toml", PACKAGE),
+    ]);
+    let err = find(&tempdir, "other/foo/Cargo.toml").unwrap_err();
+    assert!(matches!(
+        err.without_location(),
+        Error::NotAWorkspaceMember { .. }
+    ));
+    let location = err.location().unwrap();
+    assert_eq!(location.path.as_deref(), Some(Path::new("Cargo.toml")));
+    assert_eq!(location.key.as_deref(), Some("workspace.members"));
+    assert_eq!(location.line, Some(2));
+}
+
+#[test]
+fn excluded_member() {
+    let tempdir = prepare(&[
+        (
+            "Cargo.toml",
+            "[workspace]\nmembers = [\"crates/*\"]\nexclude = [\"vendor\"]",
+        ),
+        ("vendor/foo/Cargo.toml", PACKAGE),
+    ]);
+    assert_eq!(
+        find(&tempdir, "vendor/foo/Cargo.toml").unwrap(),
+        WorkspaceMembership::Standalone
+    );
+}
+
+#[test]
+fn exclude_takes_precedence_over_globs() {
+    let tempdir = prepare(&[
+        (
+            "Cargo.toml",
+            "[workspace]\nmembers = [\"crates/*\", \"crates/vendored/bar\"]\nexclude = [\"crates/vendored\"]",
+        ),
+        ("crates/vendored/Cargo.toml", PACKAGE),
+        ("crates/vendored/bar/Cargo.toml", PACKAGE),
+        ("crates/foo/Cargo.toml", PACKAGE),
+    ]);
+    let member = WorkspaceMembership::Member {
+        root_manifest_path: PathBuf::from("Cargo.toml"),
+    };
+    assert_eq!(find(&tempdir, "crates/foo/Cargo.toml").unwrap(), member);
+    // Matched by `crates/*`, but excluded
+    assert_eq!(
+        find(&tempdir, "crates/vendored/Cargo.toml").unwrap(),
+        WorkspaceMembership::Standalone
+    );
+    // Listed explicitly, which takes precedence over `exclude`
+    assert_eq!(
+        find(&tempdir, "crates/vendored/bar/Cargo.toml").unwrap(),
+        member
+    );
+}
+
+#[test]
+fn explicit_workspace_pointer() {
+    let tempdir = prepare(&[
+        ("Cargo.toml", "[workspace]\nmembers = [\"crates/*\"]"),
+        (
+            "ws/Cargo.toml",
+            "[workspace]\nmembers = [\"../crates/foo\"]",
+        ),
+        (
+            "crates/foo/Cargo.toml",
+            "[package]\nname = \"foo\"\nworkspace = \"../../ws\"",
+        ),
+        (
+            "crates/bar/Cargo.toml",
+            "[package]\nname = \"bar\"\nworkspace = \"../..\"",
+        ),
+        (
+            "crates/baz/Cargo.toml",
+            "[package]\nname = \"baz\"\nworkspace = \"..\"",
+        ),
+    ]);
+    assert_eq!(
+        find(&tempdir, "crates/foo/Cargo.toml").unwrap(),
+        WorkspaceMembership::Member {
+            root_manifest_path: PathBuf::from("ws/Cargo.toml")
+        }
+    );
+    assert_eq!(
+        find(&tempdir, "crates/bar/Cargo.toml").unwrap(),
+        WorkspaceMembership::Member {
+            root_manifest_path: PathBuf::from("Cargo.toml")
+        }
+    );
+    let err = find(&tempdir, "crates/baz/Cargo.toml").unwrap_err();
+    assert!(matches!(err.without_location(), Error::Io(_)));
+    let location = err.location().unwrap();
+    assert_eq!(
+        location.path.as_deref(),
+        Some(Path::new("crates/baz/Cargo.toml"))
+    );
+    assert_eq!(location.key.as_deref(), Some("package.workspace"));
+    assert_eq!(location.line, Some(3));
+}
+
+#[test]
+fn load_members() {
+    let tempdir = prepare(&[
+        (
+            "Cargo.toml",
+            r#"
+[package]
+name = "root"
+version = "0.1.0"
+
+[workspace]
+members = ["crates/*", "tools/cli"]
+exclude = ["crates/experimental"]
+default-members = ["crates/foo", "tools/*"]
+
+[dependencies]
+helper = { path = "helper" }
+"#,
+        ),
+        ("src/lib.rs", ""),
+        ("crates/foo/Cargo.toml", PACKAGE),
+        ("crates/foo/src/main.rs", ""),
+        ("crates/bar/Cargo.toml", PACKAGE),
+        ("crates/experimental/Cargo.toml", PACKAGE),
+        ("crates/README.md", ""),
+        ("tools/cli/Cargo.toml", PACKAGE),
+        ("helper/Cargo.toml", PACKAGE),
+    ]);
+    let fs = Filesystem::new(tempdir.path());
+    let workspace = WorkspaceLoader::new(&fs, "").load::<Value>().unwrap();
+
+    let members = workspace.members.keys().cloned().collect::<Vec<_>>();
+    assert_eq!(
+        members,
+        vec![
+            PathBuf::from(""),
+            PathBuf::from("crates/bar"),
+            PathBuf::from("crates/foo"),
+            PathBuf::from("helper"),
+            PathBuf::from("tools/cli"),
+        ]
+    );
+    assert_eq!(
+        workspace.default_members,
+        vec![PathBuf::from("crates/foo"), PathBuf::from("tools/cli")]
+    );
+
+    assert!(workspace.members[Path::new("")].lib.is_some());
+    assert_eq!(workspace.members[Path::new("crates/foo")].bin.len(), 1);
+}
+
+#[test]
+fn load_recursive_glob_members() {
+    let tempdir = prepare(&[
+        ("Cargo.toml", "[workspace]\nmembers = [\"plugins/**/impl\"]"),
+        ("plugins/impl/Cargo.toml", PACKAGE),
+        ("plugins/foo/impl/Cargo.toml", PACKAGE),
+        ("plugins/foo/bar/impl/Cargo.toml", PACKAGE),
+        ("plugins/foo/README.md", ""),
+    ]);
+    let fs = Filesystem::new(tempdir.path());
+    let workspace = WorkspaceLoader::new(&fs, "").load::<Value>().unwrap();
+
+    let members = workspace.members.keys().cloned().collect::<Vec<_>>();
+    assert_eq!(
+        members,
+        vec![
+            PathBuf::from("plugins/foo/bar/impl"),
+            PathBuf::from("plugins/foo/impl"),
+            PathBuf::from("plugins/impl"),
+        ]
+    );
+    assert_eq!(
+        find(&tempdir, "plugins/foo/bar/impl/Cargo.toml").unwrap(),
+        WorkspaceMembership::Member {
+            root_manifest_path: PathBuf::from("Cargo.toml")
+        }
+    );
+}
+
+#[test]
+fn load_member_without_manifest() {
+    let tempdir = prepare(&[
+        ("Cargo.toml", "[workspace]\nmembers = [\"crates/*\"]"),
+        ("crates/foo/Cargo.toml", PACKAGE),
+        ("crates/bar/src/lib.rs", ""),
+    ]);
+    let fs = Filesystem::new(tempdir.path());
+    let err = WorkspaceLoader::new(&fs, "").load::<Value>().unwrap_err();
+    assert!(matches!(
+        err.without_location(),
+        Error::MissingMemberManifest { ref path } if path == Path::new("crates/bar")
+    ));
+    let location = err.location().unwrap();
+    assert_eq!(location.path.as_deref(), Some(Path::new("Cargo.toml")));
+}